@@ -1,75 +1,233 @@
+use crate::due::Due;
+use crate::priority::Priority;
+use crate::recur::Recurrence;
+use crate::status::Status;
+use crate::time::TimeEntry;
+use chrono::{DateTime, Duration, Local};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::error::Error;
 use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub enum TaskError {
-    EmptyStringError,
+    /// Reading or writing the backing storage failed.
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Stored data could not be parsed. Lines and columns start at 1.
+    Parse {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+    /// A task field holds a value that is not allowed.
+    Validation {
+        field: &'static str,
+        reason: String,
+    },
+    NotFound {
+        id: usize,
+    },
+    /// The data was written by a newer version of the app.
+    SchemaVersion {
+        found: u64,
+        supported: u64,
+    },
+    /// The data file was changed by another process since it was last read.
+    Conflict {
+        path: PathBuf,
+    },
+    /// The task can't be completed while the tasks in `by` are open.
+    Blocked {
+        id: usize,
+        by: Vec<usize>,
+    },
+    /// A dependency would make tasks wait on each other. `ids` walks the
+    /// cycle and ends where it starts.
+    Cycle {
+        ids: Vec<usize>,
+    },
+    /// A filter query could not be parsed. Columns start at 1.
+    Query {
+        column: usize,
+        reason: String,
+    },
 }
 
 // This is required so `TaskError` can implement `Error`.
 impl fmt::Display for TaskError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let description = match *self {
-            TaskError::EmptyStringError => "text is empty",
-        };
-        f.write_str(description)
+        match self {
+            TaskError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            TaskError::Parse {
+                line,
+                column,
+                reason,
+            } => write!(f, "line {}, column {}: {}", line, column, reason),
+            TaskError::Validation { field, reason } => write!(f, "{}: {}", field, reason),
+            TaskError::NotFound { id } => write!(f, "no task with id {}", id),
+            TaskError::SchemaVersion { found, supported } => write!(
+                f,
+                "data was written with schema version {}, but this version of todo-app only understands up to {}; please upgrade",
+                found, supported
+            ),
+            TaskError::Conflict { path } => {
+                write!(f, "{} was changed by another process", path.display())
+            }
+            TaskError::Blocked { id, by } => write!(
+                f,
+                "task {} is blocked by unfinished task {}",
+                id,
+                join_ids(by, ", ")
+            ),
+            TaskError::Cycle { ids } => {
+                write!(f, "dependency cycle: {}", join_ids(ids, " -> "))
+            }
+            TaskError::Query { column, reason } => {
+                write!(f, "invalid filter at column {}: {}", column, reason)
+            }
+        }
     }
 }
+fn join_ids(ids: &[usize], separator: &str) -> String {
+    ids.iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
 // And this is required to use the `?` operator.
-impl Error for TaskError {}
+impl Error for TaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TaskError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+impl TaskError {
+    pub fn io(path: &Path) -> impl FnOnce(io::Error) -> TaskError + '_ {
+        move |source| TaskError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    fn empty(field: &'static str) -> TaskError {
+        TaskError::Validation {
+            field,
+            reason: String::from("text is empty"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Task {
     id: usize,
     description: String,
-    completed: bool,
+    status: Status,
     body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<Due>,
+    #[serde(default, skip_serializing_if = "Priority::is_none")]
+    priority: Priority,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    /// Id of the task this one is a step of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<usize>,
+    /// Ids of the tasks that have to be done before this one.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    blocked_by: BTreeSet<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Local>>,
+    /// Oldest first; only the last one can still be running.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    time_entries: Vec<TimeEntry>,
+}
+
+/// Moments recorded on a task. Tasks saved before they were recorded have
+/// none of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stamp {
+    Created,
+    Updated,
+    Completed,
+}
+
+impl Stamp {
+    pub const ALL: [Stamp; 3] = [Stamp::Created, Stamp::Updated, Stamp::Completed];
+}
+
+impl fmt::Display for Stamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stamp::Created => "created",
+            Stamp::Updated => "updated",
+            Stamp::Completed => "completed",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Stamp {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Stamp, TaskError> {
+        Stamp::ALL
+            .into_iter()
+            .find(|stamp| stamp.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| TaskError::Validation {
+                field: "stamp",
+                reason: format!("unknown `{}`, expected created, updated or completed", s),
+            })
+    }
 }
 
 impl Task {
     pub fn new(id: usize, description: String, body: String) -> Result<Task, TaskError> {
         if description.is_empty() {
-            return Err(TaskError::EmptyStringError);
+            return Err(TaskError::empty("description"));
         }
         Ok(Task {
             id,
             description,
             body,
-            completed: false,
+            ..Default::default()
         })
     }
 
     pub fn from_description(input: &str) -> Result<Task, TaskError> {
+        if input.is_empty() {
+            return Err(TaskError::empty("description"));
+        }
         Ok(Task {
             description: input.to_owned(),
             ..Default::default()
         })
     }
 
-    pub fn to_line(&self) -> String {
-        format!("{},{},{}", self.id, self.description, self.completed)
-    }
-
-    pub fn from_line(line: &str) -> Result<Task, TaskError> {
-        let parts: Vec<&str> = line.rsplit(",").collect();
-        match parts.len() == 4 {
-            true => {
-                return Ok(Task {
-                    id: parts[3].parse::<usize>().unwrap(),
-                    description: parts[2].parse::<String>().unwrap(),
-                    completed: parts[1].parse::<bool>().unwrap(),
-                    body: parts[0].parse::<String>().unwrap(),
-                });
-            }
-            false => {
-                return Err(TaskError::EmptyStringError);
-            }
-        }
+    /// Whether the task is closed, either done or cancelled.
+    pub fn completed(&self) -> bool {
+        self.status.is_closed()
     }
 
-    pub fn completed(&self) -> bool {
-        self.completed
+    pub fn status(&self) -> Status {
+        self.status
     }
 
     pub fn get_id(&self) -> usize {
@@ -90,13 +248,148 @@ impl Task {
 
     pub fn set_body(&mut self, body: String) {
         self.body = body;
+        self.touch();
     }
 
-    pub fn set_completed(&mut self) {
-        self.completed = !self.completed;
+    /// Moves the task to `status`, recording when it was closed.
+    pub fn set_status(&mut self, status: Status) {
+        if status.is_closed() != self.status.is_closed() {
+            self.completed_at = status.is_closed().then(Local::now);
+        }
+        self.status = status;
+        self.touch();
     }
 
     pub fn set_description(&mut self, input: String) {
         self.description = input;
+        self.touch();
+    }
+
+    pub fn due(&self) -> Option<Due> {
+        self.due
+    }
+
+    pub fn set_due(&mut self, due: Option<Due>) {
+        self.due = due;
+        self.touch();
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.touch();
+    }
+
+    pub fn tags(&self) -> &BTreeSet<String> {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: BTreeSet<String>) {
+        self.tags = tags;
+        self.touch();
+    }
+
+    pub fn project(&self) -> Option<&str> {
+        self.project.as_deref()
+    }
+
+    pub fn set_project(&mut self, project: Option<String>) {
+        self.project = project;
+        self.touch();
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn set_parent(&mut self, parent: Option<usize>) {
+        self.parent = parent;
+        self.touch();
+    }
+
+    pub fn blocked_by(&self) -> &BTreeSet<usize> {
+        &self.blocked_by
+    }
+
+    pub fn set_blocked_by(&mut self, blocked_by: BTreeSet<usize>) {
+        self.blocked_by = blocked_by;
+        self.touch();
+    }
+
+    pub fn recurrence(&self) -> Option<&Recurrence> {
+        self.recurrence.as_ref()
+    }
+
+    pub fn set_recurrence(&mut self, recurrence: Option<Recurrence>) {
+        self.recurrence = recurrence;
+        self.touch();
+    }
+
+    pub fn time_entries(&self) -> &[TimeEntry] {
+        &self.time_entries
+    }
+
+    pub fn set_time_entries(&mut self, time_entries: Vec<TimeEntry>) {
+        self.time_entries = time_entries;
+        self.touch();
+    }
+
+    /// Whether a timer is running on the task.
+    pub fn is_tracking(&self) -> bool {
+        self.time_entries
+            .last()
+            .is_some_and(|entry| entry.end.is_none())
+    }
+
+    /// Starts a timer at `at`, unless one is already running.
+    pub fn start_timer(&mut self, at: DateTime<Local>) {
+        if !self.is_tracking() {
+            self.time_entries.push(TimeEntry {
+                start: at,
+                end: None,
+            });
+            self.touch();
+        }
+    }
+
+    /// Stops the running timer at `at`, if there is one.
+    pub fn stop_timer(&mut self, at: DateTime<Local>) {
+        if let Some(entry) = self.time_entries.last_mut().filter(|e| e.end.is_none()) {
+            entry.end = Some(at.max(entry.start));
+            self.touch();
+        }
+    }
+
+    /// Total time spent on the task, a running timer counting up to `now`.
+    pub fn tracked(&self, now: DateTime<Local>) -> Duration {
+        self.time_entries
+            .iter()
+            .map(|entry| entry.duration(now))
+            .sum()
+    }
+
+    pub fn stamp(&self, stamp: Stamp) -> Option<DateTime<Local>> {
+        match stamp {
+            Stamp::Created => self.created_at,
+            Stamp::Updated => self.updated_at,
+            Stamp::Completed => self.completed_at,
+        }
+    }
+
+    /// Overwrites a timestamp, for loading stored tasks. The setters keep
+    /// them up to date otherwise.
+    pub fn set_stamp(&mut self, stamp: Stamp, at: Option<DateTime<Local>>) {
+        match stamp {
+            Stamp::Created => self.created_at = at,
+            Stamp::Updated => self.updated_at = at,
+            Stamp::Completed => self.completed_at = at,
+        }
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Some(Local::now());
     }
 }
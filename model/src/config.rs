@@ -0,0 +1,94 @@
+use crate::common::TaskError;
+use crate::query::Query;
+use crate::status::Status;
+use crate::store::write_atomic;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// User settings, kept as JSON next to the data file. Settings missing from
+/// the file keep their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Statuses `Tab` steps through, in order. Statuses left out can still be
+    /// set from the command line.
+    pub status_cycle: Vec<Status>,
+    /// Queries listed in the sidebar under their own name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<SavedView>,
+}
+
+/// A filter query kept under a name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    pub query: String,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            status_cycle: vec![Status::Todo, Status::InProgress, Status::Done],
+            views: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config at `path`; a missing file means the defaults.
+    pub fn load(path: &Path) -> Result<Config, TaskError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(TaskError::io(path)(err)),
+        };
+        let config: Config = serde_json::from_str(&content).map_err(|e| TaskError::Parse {
+            line: e.line(),
+            column: e.column(),
+            reason: e.to_string(),
+        })?;
+        if config.status_cycle.is_empty() {
+            return Err(TaskError::Validation {
+                field: "status_cycle",
+                reason: String::from("needs at least one status"),
+            });
+        }
+        Ok(config)
+    }
+
+    /// Why the saved views whose query does not parse are left out of the
+    /// sidebar. They stay in the config so saving does not lose them.
+    pub fn view_errors(&self) -> Vec<TaskError> {
+        let now = Local::now().naive_local();
+        self.views
+            .iter()
+            .filter_map(|view| {
+                let err = Query::parse(&view.query, now).err()?;
+                Some(TaskError::Validation {
+                    field: "views",
+                    reason: format!("skipped `{}`, {}", view.name, err),
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the config to `path` as pretty-printed JSON, atomically like
+    /// the task files.
+    pub fn save(&self, path: &Path) -> Result<(), TaskError> {
+        let content = serde_json::to_string_pretty(self).expect("config always serializes");
+        write_atomic(path, (content + "\n").as_bytes()).map_err(TaskError::io(path))
+    }
+
+    /// Status after `status` in the cycle, wrapping around. A status outside
+    /// the cycle moves to its first entry.
+    pub fn next_status(&self, status: Status) -> Status {
+        let cycle = &self.status_cycle;
+        match cycle.iter().position(|&s| s == status) {
+            Some(i) => cycle[(i + 1) % cycle.len()],
+            None => cycle.first().copied().unwrap_or_default(),
+        }
+    }
+}
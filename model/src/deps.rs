@@ -0,0 +1,36 @@
+use crate::common::Task;
+use std::collections::{HashMap, HashSet};
+
+/// Looks for the cycle that making task `id` wait on task `blocker` would
+/// close. Returns the ids along it, starting and ending at `id`.
+pub fn find_cycle(tasks: &[Task], id: usize, blocker: usize) -> Option<Vec<usize>> {
+    let edges: HashMap<usize, &Task> = tasks.iter().map(|task| (task.get_id(), task)).collect();
+
+    let mut path = vec![id, blocker];
+    if blocker == id {
+        return Some(path);
+    }
+
+    // depth first from `blocker`, following what each task waits on
+    let mut pending = vec![edges.get(&blocker)?.blocked_by().iter()];
+    let mut seen = HashSet::from([blocker]);
+    while let Some(next) = pending.last_mut() {
+        match next.next() {
+            Some(&next) if next == id => {
+                path.push(id);
+                return Some(path);
+            }
+            Some(&next) => {
+                if let Some(task) = edges.get(&next).filter(|_| seen.insert(next)) {
+                    path.push(next);
+                    pending.push(task.blocked_by().iter());
+                }
+            }
+            None => {
+                pending.pop();
+                path.pop();
+            }
+        }
+    }
+    None
+}
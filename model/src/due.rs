@@ -0,0 +1,151 @@
+use crate::common::TaskError;
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// When a task is due, either on a whole day or at a given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Due {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Due {
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Due::Date(date) => *date,
+            Due::DateTime(date_time) => date_time.date(),
+        }
+    }
+
+    /// Latest moment that still counts as on time.
+    pub fn deadline(&self) -> NaiveDateTime {
+        match self {
+            Due::Date(date) => date.and_time(NaiveTime::MIN) + Days::new(1),
+            Due::DateTime(date_time) => *date_time,
+        }
+    }
+
+    pub fn is_overdue(&self, now: NaiveDateTime) -> bool {
+        self.deadline() <= now
+    }
+
+    pub fn is_due_today(&self, now: NaiveDateTime) -> bool {
+        self.date() == now.date() && !self.is_overdue(now)
+    }
+
+    /// Short distance to the due date, like `today`, `in 2d` or `3d late`.
+    pub fn relative(&self, now: NaiveDateTime) -> String {
+        let days = (self.date() - now.date()).num_days();
+        match days {
+            0 if self.is_overdue(now) => String::from("late"),
+            0 => String::from("today"),
+            days if days > 0 => format!("in {}d", days),
+            days => format!("{}d late", -days),
+        }
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Due::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Due::DateTime(date_time) => write!(f, "{}", date_time.format("%Y-%m-%d %H:%M")),
+        }
+    }
+}
+
+/// Parses the `Display` form back.
+impl FromStr for Due {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Due, TaskError> {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M") {
+            return Ok(Due::DateTime(date_time));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Due::Date)
+            .map_err(|_| invalid(format!("invalid date `{}`", s)))
+    }
+}
+
+/// Parses a due date relative to `now`.
+///
+/// Accepts `today`, `tomorrow`, `yesterday`, weekday names (`fri`, `friday`;
+/// always the next one after today), offsets (`+3d`, `+2w`, `+1m`, or `-3d`
+/// into the past) and ISO dates, each optionally followed by a time (`14:30`).
+/// ISO date-times may also use a `T` separator. An empty input clears the due
+/// date.
+pub fn parse_due(input: &str, now: NaiveDateTime) -> Result<Option<Due>, TaskError> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%dt%H:%M") {
+        return Ok(Some(Due::DateTime(date_time)));
+    }
+
+    let mut words = input.split_whitespace();
+    let day = words.next().unwrap_or_default();
+    let date =
+        parse_day(day, now.date()).ok_or_else(|| invalid(format!("unknown date `{}`", day)))?;
+
+    match (words.next(), words.next()) {
+        (None, _) => Ok(Some(Due::Date(date))),
+        (Some(time), None) => {
+            let time = NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| invalid(format!("unknown time `{}`", time)))?;
+            Ok(Some(Due::DateTime(date.and_time(time))))
+        }
+        (Some(_), Some(extra)) => Err(invalid(format!("unexpected `{}`", extra))),
+    }
+}
+
+fn parse_day(day: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match day {
+        "today" => return Some(today),
+        "tomorrow" => return today.checked_add_days(Days::new(1)),
+        "yesterday" => return today.checked_sub_days(Days::new(1)),
+        _ => {}
+    }
+    if let Some(offset) = day.strip_prefix('-') {
+        return shift(today, offset, false);
+    }
+    if let Some(offset) = day.strip_prefix('+') {
+        return shift(today, offset, true);
+    }
+    if let Ok(weekday) = day.parse::<Weekday>() {
+        let ahead =
+            (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+        let ahead = if ahead == 0 { 7 } else { ahead };
+        return today.checked_add_days(Days::new(ahead.into()));
+    }
+    NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()
+}
+
+/// `today` moved by an offset like `3d`, `2w` or `1m`, ahead or back.
+fn shift(today: NaiveDate, offset: &str, ahead: bool) -> Option<NaiveDate> {
+    let unit = offset.chars().next_back()?;
+    let amount: u32 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+    let days = match unit {
+        'd' => Days::new(amount.into()),
+        'w' => Days::new(u64::from(amount) * 7),
+        'm' if ahead => return today.checked_add_months(Months::new(amount)),
+        'm' => return today.checked_sub_months(Months::new(amount)),
+        _ => return None,
+    };
+    if ahead {
+        today.checked_add_days(days)
+    } else {
+        today.checked_sub_days(days)
+    }
+}
+
+fn invalid(reason: String) -> TaskError {
+    TaskError::Validation {
+        field: "due",
+        reason,
+    }
+}
@@ -1,2 +1,15 @@
 pub mod common;
+pub mod config;
+pub mod deps;
+pub mod due;
+pub mod line;
+pub mod priority;
+pub mod query;
+pub mod recur;
+pub mod schema;
+pub mod search;
+pub mod status;
+pub mod store;
+pub mod tags;
+pub mod time;
 pub mod util;
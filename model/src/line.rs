@@ -0,0 +1,261 @@
+use crate::common::{Stamp, Task, TaskError};
+use crate::due::Due;
+use crate::priority::Priority;
+use crate::recur::Recurrence;
+use crate::status::Status;
+use crate::time::TimeEntry;
+use chrono::{DateTime, Local};
+
+/// Encodes a task as a single tab separated record:
+/// `id`, `status`, `description`, `body`, `due`, `priority`, `tags`
+/// (separated by spaces), `project`, `parent`, `blocked_by` (ids separated
+/// by spaces), `recurrence`, `created_at`, `updated_at` and `completed_at`
+/// (RFC 3339) and `time_entries` (`start/end` pairs separated by spaces).
+///
+/// Fields added later are appended at the end and may be missing from older
+/// records.
+///
+/// Backslashes, tabs and line breaks inside fields are escaped as `\\`, `\t`,
+/// `\n` and `\r`, so any description or body round-trips on one line.
+pub fn encode(task: &Task) -> String {
+    [
+        task.get_id().to_string(),
+        task.status().to_string(),
+        escape(&task.description()),
+        escape(&task.body()),
+        task.due().map(|due| due.to_string()).unwrap_or_default(),
+        priority(task.priority()),
+        escape(&tags(task)),
+        escape(task.project().unwrap_or_default()),
+        task.parent().map(|id| id.to_string()).unwrap_or_default(),
+        blocked_by(task),
+        task.recurrence()
+            .map(|recurrence| recurrence.to_string())
+            .unwrap_or_default(),
+        stamp(task, Stamp::Created),
+        stamp(task, Stamp::Updated),
+        stamp(task, Stamp::Completed),
+        time_entries(task),
+    ]
+    .join("\t")
+}
+
+/// Parses a record written by `encode`. `line_number` is only used to report
+/// where a malformed record was found.
+pub fn decode(line: &str, line_number: usize) -> Result<Task, TaskError> {
+    let fields = split(line, line_number)?;
+    if fields.len() < 4 {
+        return Err(TaskError::Parse {
+            line: line_number,
+            column: line.chars().count() + 1,
+            reason: format!("expected 4 fields, found {}", fields.len()),
+        });
+    }
+
+    let (column, id) = &fields[0];
+    let id = id.parse::<usize>().map_err(|_| TaskError::Parse {
+        line: line_number,
+        column: *column,
+        reason: format!("invalid id `{}`", id),
+    })?;
+
+    // records written before statuses existed hold a completed flag
+    let (column, status) = &fields[1];
+    let status = match status.as_str() {
+        "true" => Ok(Status::Done),
+        "false" => Ok(Status::Todo),
+        status => status.parse::<Status>(),
+    }
+    .map_err(|e| TaskError::Parse {
+        line: line_number,
+        column: *column,
+        reason: e.to_string(),
+    })?;
+
+    let (column, description) = &fields[2];
+    let mut task =
+        Task::new(id, description.clone(), fields[3].1.clone()).map_err(|e| TaskError::Parse {
+            line: line_number,
+            column: *column,
+            reason: e.to_string(),
+        })?;
+    task.set_status(status);
+
+    if let Some((column, due)) = fields.get(4).filter(|(_, due)| !due.is_empty()) {
+        let due = due.parse::<Due>().map_err(|e| TaskError::Parse {
+            line: line_number,
+            column: *column,
+            reason: e.to_string(),
+        })?;
+        task.set_due(Some(due));
+    }
+
+    if let Some((column, priority)) = fields.get(5) {
+        let priority = priority.parse::<Priority>().map_err(|e| TaskError::Parse {
+            line: line_number,
+            column: *column,
+            reason: e.to_string(),
+        })?;
+        task.set_priority(priority);
+    }
+
+    if let Some((_, tags)) = fields.get(6) {
+        task.set_tags(tags.split_whitespace().map(str::to_owned).collect());
+    }
+    if let Some((_, project)) = fields.get(7).filter(|(_, project)| !project.is_empty()) {
+        task.set_project(Some(project.clone()));
+    }
+    if let Some((column, parent)) = fields.get(8).filter(|(_, parent)| !parent.is_empty()) {
+        let parent = parent.parse::<usize>().map_err(|_| TaskError::Parse {
+            line: line_number,
+            column: *column,
+            reason: format!("invalid parent id `{}`", parent),
+        })?;
+        task.set_parent(Some(parent));
+    }
+    if let Some((column, ids)) = fields.get(9) {
+        let blocked_by = ids
+            .split_whitespace()
+            .map(|id| {
+                id.parse::<usize>().map_err(|_| TaskError::Parse {
+                    line: line_number,
+                    column: *column,
+                    reason: format!("invalid blocking task id `{}`", id),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        task.set_blocked_by(blocked_by);
+    }
+    if let Some((column, rule)) = fields.get(10).filter(|(_, rule)| !rule.is_empty()) {
+        let recurrence = rule.parse::<Recurrence>().map_err(|e| TaskError::Parse {
+            line: line_number,
+            column: *column,
+            reason: e.to_string(),
+        })?;
+        task.set_recurrence(Some(recurrence));
+    }
+
+    if let Some((column, entries)) = fields.get(14) {
+        let entries = entries
+            .split_whitespace()
+            .map(|entry| {
+                entry.parse::<TimeEntry>().map_err(|e| TaskError::Parse {
+                    line: line_number,
+                    column: *column,
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        task.set_time_entries(entries);
+    }
+
+    // last, since the setters above stamp the task as updated just now
+    for (i, stamp) in Stamp::ALL.into_iter().enumerate() {
+        let at = match fields.get(11 + i) {
+            Some((column, at)) if !at.is_empty() => Some(at.parse::<DateTime<Local>>().map_err(
+                |_| TaskError::Parse {
+                    line: line_number,
+                    column: *column,
+                    reason: format!("invalid {} time `{}`", stamp, at),
+                },
+            )?),
+            _ => None,
+        };
+        task.set_stamp(stamp, at);
+    }
+    Ok(task)
+}
+
+/// A timestamp of `task` in RFC 3339, or empty when it was never recorded.
+pub fn stamp(task: &Task, stamp: Stamp) -> String {
+    task.stamp(stamp)
+        .map(|at| at.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Time entries of `task` separated by spaces.
+pub fn time_entries(task: &Task) -> String {
+    task.time_entries()
+        .iter()
+        .map(TimeEntry::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Ids of the tasks blocking `task`, separated by spaces.
+pub fn blocked_by(task: &Task) -> String {
+    task.blocked_by()
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Tags of `task` separated by spaces. Tag names never contain whitespace.
+pub fn tags(task: &Task) -> String {
+    task.tags()
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Priority column; left empty for `None` like a missing due date.
+fn priority(priority: Priority) -> String {
+    match priority {
+        Priority::None => String::new(),
+        priority => priority.to_string(),
+    }
+}
+
+/// Escapes backslashes, tabs and line breaks so `field` fits in one column.
+pub fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits a record into unescaped fields, each paired with the 1-based column
+/// it starts at.
+fn split(line: &str, line_number: usize) -> Result<Vec<(usize, String)>, TaskError> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut start = 1;
+
+    let mut chars = line.chars().enumerate();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\t' => {
+                fields.push((start, std::mem::take(&mut current)));
+                start = i + 2;
+            }
+            '\\' => match chars.next() {
+                Some((_, '\\')) => current.push('\\'),
+                Some((_, 't')) => current.push('\t'),
+                Some((_, 'n')) => current.push('\n'),
+                Some((_, 'r')) => current.push('\r'),
+                other => {
+                    return Err(TaskError::Parse {
+                        line: line_number,
+                        column: i + 1,
+                        reason: match other {
+                            Some((_, c)) => format!("unknown escape `\\{}`", c),
+                            None => String::from("line ends with a lone `\\`"),
+                        },
+                    })
+                }
+            },
+            c => current.push(c),
+        }
+    }
+    fields.push((start, current));
+    Ok(fields)
+}
@@ -0,0 +1,88 @@
+use crate::common::TaskError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How important a task is. Ordered from least to most important.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 5] = [
+        Priority::None,
+        Priority::Low,
+        Priority::Medium,
+        Priority::High,
+        Priority::Urgent,
+    ];
+
+    pub fn is_none(&self) -> bool {
+        *self == Priority::None
+    }
+
+    /// One step up, staying at `Urgent`.
+    pub fn raise(self) -> Priority {
+        Priority::ALL[(self as usize + 1).min(Priority::ALL.len() - 1)]
+    }
+
+    /// One step down, staying at `None`.
+    pub fn lower(self) -> Priority {
+        Priority::ALL[(self as usize).saturating_sub(1)]
+    }
+
+    /// Marker shown in front of the description; empty for `None`.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Priority::None => "",
+            Priority::Low => "!",
+            Priority::Medium => "!!",
+            Priority::High => "!!!",
+            Priority::Urgent => "!!!!",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parses the `Display` form, case-insensitively. An empty string is `None`.
+impl FromStr for Priority {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Priority, TaskError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Priority::None);
+        }
+        Priority::ALL
+            .into_iter()
+            .find(|priority| priority.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| TaskError::Validation {
+                field: "priority",
+                reason: format!(
+                    "unknown priority `{}`, expected none, low, medium, high or urgent",
+                    s
+                ),
+            })
+    }
+}
@@ -0,0 +1,283 @@
+use crate::common::{Task, TaskError};
+use crate::due::parse_due;
+use crate::priority::Priority;
+use crate::status::Status;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A filter typed as text, like `status:todo tag:work due<7d prio>=high
+/// "some text"`.
+///
+/// Terms are separated by spaces and a task has to match all of them; a
+/// leading `-` turns a term around. The fields are:
+///
+/// - `status:` one or more statuses separated by commas, or `open`/`closed`
+/// - `tag:` (or `#tag`, `@tag`) and `project:` (or `+project`)
+/// - `due` compared with `:`, `<`, `<=`, `>` or `>=` against a day in any
+///   form a due date takes, where a bare offset like `7d` counts ahead;
+///   `due:none` matches tasks without one
+/// - `prio` (or `priority`) compared the same way against a priority
+///
+/// Anything else, or text in double quotes, has to appear in the description
+/// or body, ignoring case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    source: String,
+    terms: Vec<(bool, Term)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Status(Vec<Status>),
+    Tag(String),
+    Project(String),
+    /// `None` stands for tasks without a due date.
+    Due(Compare, Option<NaiveDate>),
+    Priority(Compare, Priority),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compare {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Compare {
+    /// Operators by length, so `<=` is found before `<`.
+    const OPERATORS: [(&'static str, Compare); 6] = [
+        ("<=", Compare::Le),
+        (">=", Compare::Ge),
+        (":", Compare::Eq),
+        ("=", Compare::Eq),
+        ("<", Compare::Lt),
+        (">", Compare::Gt),
+    ];
+
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Compare::Eq => ordering.is_eq(),
+            Compare::Lt => ordering.is_lt(),
+            Compare::Le => ordering.is_le(),
+            Compare::Gt => ordering.is_gt(),
+            Compare::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+impl Query {
+    /// Parses `input`, reading relative days from `now`. An empty input
+    /// matches every task.
+    pub fn parse(input: &str, now: NaiveDateTime) -> Result<Query, TaskError> {
+        let terms = words(input)?
+            .into_iter()
+            .map(|word| word.parse(now))
+            .collect::<Result<_, _>>()?;
+        Ok(Query {
+            source: input.trim().to_owned(),
+            terms,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Whether the query asks for done or cancelled tasks, which are listed
+    /// then even while closed tasks are hidden.
+    pub fn shows_closed(&self) -> bool {
+        self.terms.iter().any(|term| match term {
+            (false, Term::Status(statuses)) => statuses.iter().any(Status::is_closed),
+            _ => false,
+        })
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.terms
+            .iter()
+            .all(|(negated, term)| term.matches(task) != *negated)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Term {
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            Term::Status(statuses) => statuses.contains(&task.status()),
+            Term::Tag(name) => task.tags().iter().any(|tag| tag.eq_ignore_ascii_case(name)),
+            Term::Project(name) => task
+                .project()
+                .is_some_and(|project| project.eq_ignore_ascii_case(name)),
+            Term::Due(_, None) => task.due().is_none(),
+            Term::Due(compare, Some(day)) => task
+                .due()
+                .is_some_and(|due| compare.holds(due.date().cmp(day))),
+            Term::Priority(compare, priority) => compare.holds(task.priority().cmp(priority)),
+            Term::Text(text) => {
+                let text = text.to_lowercase();
+                task.description().to_lowercase().contains(&text)
+                    || task.body().to_lowercase().contains(&text)
+            }
+        }
+    }
+}
+
+/// A term as typed, with the column it starts at for error messages.
+struct Word {
+    column: usize,
+    negated: bool,
+    quoted: bool,
+    text: String,
+}
+
+/// Splits the input at spaces outside double quotes.
+fn words(input: &str) -> Result<Vec<Word>, TaskError> {
+    let mut words = Vec::new();
+    let mut chars = input.chars().enumerate().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut word = Word {
+            column: i + 1,
+            negated: false,
+            quoted: false,
+            text: String::new(),
+        };
+        let mut c = c;
+        if c == '-' {
+            match chars.next_if(|(_, next)| !next.is_whitespace()) {
+                Some((_, next)) => {
+                    word.negated = true;
+                    c = next;
+                }
+                None => return Err(invalid(i + 1, "`-` needs a term after it")),
+            }
+        }
+        if c == '"' {
+            word.quoted = true;
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, c)) => word.text.push(c),
+                    None => return Err(invalid(word.column, "unterminated quote")),
+                }
+            }
+        } else {
+            word.text.push(c);
+            while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace()) {
+                word.text.push(c);
+            }
+        }
+        words.push(word);
+    }
+    Ok(words)
+}
+
+impl Word {
+    fn parse(self, now: NaiveDateTime) -> Result<(bool, Term), TaskError> {
+        if self.quoted {
+            return Ok((self.negated, Term::Text(self.text)));
+        }
+        let column = self.column;
+        let field = Compare::OPERATORS
+            .iter()
+            .filter_map(|&(operator, compare)| {
+                let at = self.text.find(operator)?;
+                Some((at, operator, compare))
+            })
+            .min_by_key(|&(at, operator, _)| (at, std::cmp::Reverse(operator.len())));
+        let term = match field {
+            Some((at, operator, compare)) => {
+                let key = self.text[..at].to_lowercase();
+                let value = &self.text[at + operator.len()..];
+                if value.is_empty() {
+                    return Err(invalid(column, format!("`{}` needs a value", key)));
+                }
+                field_term(&key, compare, value, now).map_err(|reason| invalid(column, reason))?
+            }
+            None => {
+                let tag = self.text.strip_prefix(['#', '@']);
+                let project = self.text.strip_prefix('+');
+                match (tag, project) {
+                    (Some(name), _) if !name.is_empty() => Term::Tag(name.to_owned()),
+                    (_, Some(name)) if !name.is_empty() => Term::Project(name.to_owned()),
+                    _ => Term::Text(self.text),
+                }
+            }
+        };
+        Ok((self.negated, term))
+    }
+}
+
+fn field_term(
+    key: &str,
+    compare: Compare,
+    value: &str,
+    now: NaiveDateTime,
+) -> Result<Term, String> {
+    let equality_only = |term: Term| match compare {
+        Compare::Eq => Ok(term),
+        _ => Err(format!("`{}` can only be matched with `:`", key)),
+    };
+    match key {
+        "status" => {
+            let statuses = value
+                .split(',')
+                .flat_map(|name| match name.to_lowercase().as_str() {
+                    "open" => Status::ALL
+                        .iter()
+                        .filter(|s| !s.is_closed())
+                        .map(|&s| Ok(s))
+                        .collect(),
+                    "closed" => Status::ALL
+                        .iter()
+                        .filter(|s| s.is_closed())
+                        .map(|&s| Ok(s))
+                        .collect(),
+                    _ => vec![name.parse::<Status>().map_err(|e| e.to_string())],
+                })
+                .collect::<Result<_, _>>()?;
+            equality_only(Term::Status(statuses))
+        }
+        "tag" => equality_only(Term::Tag(value.to_owned())),
+        "project" => equality_only(Term::Project(value.to_owned())),
+        "due" if value.eq_ignore_ascii_case("none") => equality_only(Term::Due(compare, None)),
+        "due" => {
+            // a bare offset counts ahead, as `+7d` would
+            let value = if value.starts_with(|c: char| c.is_ascii_digit())
+                && value.ends_with(['d', 'w', 'm'])
+            {
+                format!("+{}", value)
+            } else {
+                value.to_owned()
+            };
+            let due = parse_due(&value, now).map_err(|e| e.to_string())?;
+            Ok(Term::Due(compare, due.map(|due| due.date())))
+        }
+        "prio" | "priority" => {
+            let priority = value.parse::<Priority>().map_err(|e| e.to_string())?;
+            Ok(Term::Priority(compare, priority))
+        }
+        key => Err(format!(
+            "unknown field `{}`, expected status, tag, project, due or prio",
+            key
+        )),
+    }
+}
+
+fn invalid(column: usize, reason: impl Into<String>) -> TaskError {
+    TaskError::Query {
+        column,
+        reason: reason.into(),
+    }
+}
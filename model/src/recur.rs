@@ -0,0 +1,178 @@
+use crate::common::{Stamp, Task, TaskError};
+use crate::due::Due;
+use crate::status::Status;
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// How a task comes back once it is done. Stored in its `Display` form, like
+/// `weekly mon,thu`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Recurrence {
+    /// `daily`
+    Daily,
+    /// `weekly mon,thu`; without days, on the weekday of the last due date.
+    Weekly(BTreeSet<u32>),
+    /// `monthly 15`; without a day, on the day of the last due date. Days past
+    /// the end of a month fall on its last day.
+    Monthly(Option<u32>),
+    /// `after 3d`: the given number of days after it was completed.
+    After(u32),
+}
+
+impl Recurrence {
+    /// Due date of the occurrence after one due on `due` and completed at
+    /// `now`. Occurrences that would already be in the past are skipped.
+    pub fn next_due(&self, due: Option<Due>, now: NaiveDateTime) -> Option<Due> {
+        let today = now.date();
+        let last = due.map(|due| due.date()).unwrap_or(today);
+        let date = match self {
+            Recurrence::After(days) => today.checked_add_days(Days::new((*days).into()))?,
+            rule => {
+                let mut date = rule.step(last, last)?;
+                while date <= today {
+                    date = rule.step(date, last)?;
+                }
+                date
+            }
+        };
+        Some(match due {
+            Some(Due::DateTime(date_time)) => Due::DateTime(date.and_time(date_time.time())),
+            _ => Due::Date(date),
+        })
+    }
+
+    /// First matching day after `date`. `first` is the original due date,
+    /// which fills in the weekday or day of the month when none was given.
+    fn step(&self, date: NaiveDate, first: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Daily => date.checked_add_days(Days::new(1)),
+            Recurrence::Weekly(days) => {
+                let days = if days.is_empty() {
+                    BTreeSet::from([first.weekday().num_days_from_monday()])
+                } else {
+                    days.clone()
+                };
+                (1..=7)
+                    .filter_map(|ahead| date.checked_add_days(Days::new(ahead)))
+                    .find(|next| days.contains(&next.weekday().num_days_from_monday()))
+            }
+            Recurrence::Monthly(day) => {
+                let day = day.unwrap_or(first.day());
+                let month = date.with_day(1)?.checked_add_months(Months::new(1))?;
+                let candidate = month_day(date.with_day(1)?, day).filter(|next| *next > date);
+                candidate.or_else(|| month_day(month, day))
+            }
+            Recurrence::After(days) => date.checked_add_days(Days::new((*days).into())),
+        }
+    }
+}
+
+/// `day` of the month starting at `first`, clamped to its last day.
+fn month_day(first: NaiveDate, day: u32) -> Option<NaiveDate> {
+    (1..=day.min(31)).rev().find_map(|day| first.with_day(day))
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly(days) if days.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly(days) => {
+                let days: Vec<String> = days
+                    .iter()
+                    .filter_map(|&day| Weekday::try_from(day as u8).ok())
+                    .map(|day| day.to_string().to_lowercase())
+                    .collect();
+                write!(f, "weekly {}", days.join(","))
+            }
+            Recurrence::Monthly(None) => write!(f, "monthly"),
+            Recurrence::Monthly(Some(day)) => write!(f, "monthly {}", day),
+            Recurrence::After(days) => write!(f, "after {}d", days),
+        }
+    }
+}
+
+/// Parses the `Display` form. Weekdays may be separated by commas or spaces
+/// and written in full.
+impl FromStr for Recurrence {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Recurrence, TaskError> {
+        let s = s.trim().to_lowercase();
+        let (rule, rest) = s.split_once(char::is_whitespace).unwrap_or((&s, ""));
+        let rest = rest.trim();
+        match rule {
+            "daily" if rest.is_empty() => Ok(Recurrence::Daily),
+            "weekly" => rest
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|day| !day.is_empty())
+                .map(|day| {
+                    day.parse::<Weekday>()
+                        .map(|day| day.num_days_from_monday())
+                        .map_err(|_| invalid(format!("unknown weekday `{}`", day)))
+                })
+                .collect::<Result<_, _>>()
+                .map(Recurrence::Weekly),
+            "monthly" if rest.is_empty() => Ok(Recurrence::Monthly(None)),
+            "monthly" => match rest.parse::<u32>() {
+                Ok(day @ 1..=31) => Ok(Recurrence::Monthly(Some(day))),
+                _ => Err(invalid(format!("`{}` is not a day of the month", rest))),
+            },
+            "after" => match rest.strip_suffix('d').unwrap_or(rest).parse::<u32>() {
+                Ok(days) if days > 0 => Ok(Recurrence::After(days)),
+                _ => Err(invalid(format!("`{}` is not a number of days", rest))),
+            },
+            _ => Err(invalid(format!(
+                "unknown rule `{}`, expected daily, weekly, monthly or after",
+                s
+            ))),
+        }
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> String {
+        recurrence.to_string()
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = TaskError;
+
+    fn try_from(s: String) -> Result<Recurrence, TaskError> {
+        s.parse()
+    }
+}
+
+/// Parses a rule typed by the user. An empty input means no recurrence.
+pub fn parse_recurrence(input: &str) -> Result<Option<Recurrence>, TaskError> {
+    match input.trim() {
+        "" => Ok(None),
+        rule => rule.parse().map(Some),
+    }
+}
+
+/// Copy of a recurring `task` to do next, due on the following occurrence.
+/// It keeps the rule and everything describing the work, but none of the
+/// dependencies or tracked time.
+pub fn next_occurrence(task: &Task, now: NaiveDateTime) -> Option<Task> {
+    let recurrence = task.recurrence()?;
+    let mut next = task.clone();
+    next.set_status(Status::Todo);
+    next.set_due(recurrence.next_due(task.due(), now));
+    next.set_blocked_by(BTreeSet::new());
+    next.set_time_entries(Vec::new());
+    next.set_stamp(Stamp::Created, None);
+    Some(next)
+}
+
+fn invalid(reason: String) -> TaskError {
+    TaskError::Validation {
+        field: "recurrence",
+        reason,
+    }
+}
@@ -0,0 +1,141 @@
+use crate::common::{Task, TaskError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Version written by this build. Bump it together with a new entry in
+/// `MIGRATIONS` whenever the stored shape of a task changes.
+pub const SCHEMA_VERSION: u64 = 11;
+
+/// Upgrades a document from version `n` (its index) to version `n + 1`.
+type Migration = fn(Value) -> Result<Value, TaskError>;
+
+const MIGRATIONS: &[Migration] = &[
+    v0_to_v1,
+    added_fields, // v2: due
+    added_fields, // v3: priority
+    added_fields, // v4: tags, project
+    added_fields, // v5: parent
+    added_fields, // v6: blocked_by
+    added_fields, // v7: recurrence
+    added_fields, // v8: created_at, updated_at, completed_at
+    v8_to_v9,
+    v9_to_v10,
+    added_fields, // v11: time_entries
+];
+
+/// On-disk envelope around the task list.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Document {
+    pub schema_version: u64,
+    /// Id the next new task gets. Ids are never handed out twice, even after
+    /// the task holding one is removed.
+    pub next_id: usize,
+    pub tasks: Vec<Task>,
+}
+
+impl Document {
+    /// Wraps `tasks`, raising `next_id` past every id in use.
+    pub fn new(tasks: Vec<Task>, next_id: usize) -> Document {
+        Document {
+            schema_version: SCHEMA_VERSION,
+            next_id: next_free_id(&tasks, next_id),
+            tasks,
+        }
+    }
+}
+
+/// Smallest id that is at least `next_id` and above every id in `tasks`.
+pub fn next_free_id(tasks: &[Task], next_id: usize) -> usize {
+    tasks
+        .iter()
+        .map(|task| task.get_id() + 1)
+        .max()
+        .unwrap_or(0)
+        .max(next_id)
+}
+
+/// Schema version of a raw document. Files written before the envelope
+/// existed are a bare array and count as version 0.
+pub fn version_of(document: &Value) -> Result<u64, TaskError> {
+    match document {
+        Value::Array(_) => Ok(0),
+        Value::Object(map) => map
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("schema_version", String::from("missing or not a number"))),
+        _ => Err(invalid(
+            "document",
+            String::from("expected an object or an array"),
+        )),
+    }
+}
+
+/// Runs every migration needed to bring `document` to `SCHEMA_VERSION`.
+pub fn upgrade(mut document: Value) -> Result<Value, TaskError> {
+    let mut version = version_of(&document)?;
+    if version > SCHEMA_VERSION {
+        return Err(TaskError::SchemaVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    while version < SCHEMA_VERSION {
+        document = MIGRATIONS[version as usize](document)?;
+        version += 1;
+        document["schema_version"] = json!(version);
+    }
+    Ok(document)
+}
+
+/// Parses a document of any known version.
+pub fn parse(document: Value) -> Result<Document, TaskError> {
+    let document: Document =
+        serde_json::from_value(upgrade(document)?).map_err(|e| invalid("tasks", e.to_string()))?;
+    // a hand edited file may hold ids past the counter
+    Ok(Document::new(document.tasks, document.next_id))
+}
+
+fn invalid(field: &'static str, reason: String) -> TaskError {
+    TaskError::Validation { field, reason }
+}
+
+fn v0_to_v1(document: Value) -> Result<Value, TaskError> {
+    Ok(json!({ "schema_version": 1, "tasks": document }))
+}
+
+/// New optional fields need no conversion; the version bump alone keeps
+/// older builds from loading the file and dropping them on save.
+fn added_fields(document: Value) -> Result<Value, TaskError> {
+    Ok(document)
+}
+
+/// Starts the id counter after the highest id in use. Before it existed a
+/// new task took the highest id plus one, so nothing was handed out above it.
+fn v8_to_v9(mut document: Value) -> Result<Value, TaskError> {
+    let next_id = document["tasks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|task| task["id"].as_u64())
+        .map(|id| id + 1)
+        .max()
+        .unwrap_or(0);
+    document["next_id"] = json!(next_id);
+    Ok(document)
+}
+
+/// Replaces the `completed` flag with a `status`.
+fn v9_to_v10(mut document: Value) -> Result<Value, TaskError> {
+    for task in document["tasks"].as_array_mut().into_iter().flatten() {
+        let Some(task) = task.as_object_mut() else {
+            continue;
+        };
+        let completed = task.remove("completed").and_then(|c| c.as_bool());
+        let status = match completed {
+            Some(true) => "done",
+            _ => "todo",
+        };
+        task.insert(String::from("status"), json!(status));
+    }
+    Ok(document)
+}
@@ -0,0 +1,47 @@
+use crate::common::Task;
+
+/// Where a task matches a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchHit {
+    /// Positions, in characters, of the matched part of the description.
+    Description(Vec<usize>),
+    /// Only the body matches.
+    Body,
+}
+
+/// Character positions in `text` matching `query`, ignoring case and any
+/// whitespace in the query. A run of consecutive characters is preferred;
+/// otherwise the query only has to appear in order, with gaps.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    // one lowercase char per original char so positions line up
+    let lower = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lower)
+        .collect();
+    if query.is_empty() {
+        return None;
+    }
+    let text: Vec<char> = text.chars().map(lower).collect();
+
+    if let Some(start) = text.windows(query.len()).position(|w| w == query) {
+        return Some((start..start + query.len()).collect());
+    }
+    let mut positions = Vec::with_capacity(query.len());
+    let mut wanted = query.iter().peekable();
+    for (i, c) in text.iter().enumerate() {
+        if wanted.peek() == Some(&c) {
+            positions.push(i);
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none().then_some(positions)
+}
+
+/// How `task` matches `query`, looking at the description first.
+pub fn search_task(task: &Task, query: &str) -> Option<SearchHit> {
+    fuzzy_match(query, &task.description())
+        .map(SearchHit::Description)
+        .or_else(|| fuzzy_match(query, &task.body()).map(|_| SearchHit::Body))
+}
@@ -0,0 +1,77 @@
+use crate::common::TaskError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Where a task is in its workflow. `Done` and `Cancelled` close it.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    #[default]
+    Todo,
+    InProgress,
+    Waiting,
+    Done,
+    Cancelled,
+}
+
+impl Status {
+    pub const ALL: [Status; 5] = [
+        Status::Todo,
+        Status::InProgress,
+        Status::Waiting,
+        Status::Done,
+        Status::Cancelled,
+    ];
+
+    /// Whether nothing is left to do, because it was done or dropped.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Status::Done | Status::Cancelled)
+    }
+
+    /// Checkbox shown in front of the task.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Status::Todo => "[ ]",
+            Status::InProgress => "[~]",
+            Status::Waiting => "[?]",
+            Status::Done => "[x]",
+            Status::Cancelled => "[-]",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Status::Todo => "todo",
+            Status::InProgress => "in-progress",
+            Status::Waiting => "waiting",
+            Status::Done => "done",
+            Status::Cancelled => "cancelled",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parses the `Display` form, case-insensitively. `in progress` and
+/// `in_progress` work as well.
+impl FromStr for Status {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Status, TaskError> {
+        let name = s.trim().replace([' ', '_'], "-");
+        Status::ALL
+            .into_iter()
+            .find(|status| status.to_string().eq_ignore_ascii_case(&name))
+            .ok_or_else(|| TaskError::Validation {
+                field: "status",
+                reason: format!(
+                    "unknown status `{}`, expected todo, in-progress, waiting, done or cancelled",
+                    s.trim()
+                ),
+            })
+    }
+}
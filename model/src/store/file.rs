@@ -0,0 +1,238 @@
+use super::{create_parent_dir, sort_by_ids, TaskStore};
+use crate::common::{Task, TaskError};
+use crate::schema::{next_free_id, Document};
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// How a `FileStore` turns its tasks into text and back.
+pub trait TextFormat: fmt::Debug {
+    fn parse(content: &str) -> Result<Document, TaskError>;
+
+    fn render(document: &Document) -> String;
+}
+
+/// Keeps the whole list in a single text file.
+///
+/// Writes are atomic and guarded by an advisory lock on a `.lock` sidecar.
+/// The store remembers a hash of the content it last read or wrote and
+/// refuses to save with `TaskError::Conflict` if the file changed underneath
+/// it.
+#[derive(Debug)]
+pub struct FileStore<F> {
+    path: PathBuf,
+    seen: Option<u64>,
+    tasks: Vec<Task>,
+    next_id: usize,
+    format: PhantomData<F>,
+}
+
+impl<F: TextFormat> FileStore<F> {
+    pub fn new(path: PathBuf) -> Result<FileStore<F>, TaskError> {
+        create_parent_dir(&path)?;
+        if !path.exists() && !backup_path(&path).exists() {
+            File::create(&path).map_err(TaskError::io(&path))?;
+        }
+        let seen = fingerprint(&path)?;
+        Ok(FileStore {
+            path,
+            seen,
+            tasks: Vec::new(),
+            next_id: 0,
+            format: PhantomData,
+        })
+    }
+
+    fn lock(&self) -> Result<File, TaskError> {
+        let mut name = self
+            .path
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_default();
+        name.push(".lock");
+        let lock_path = self.path.with_file_name(name);
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(TaskError::io(&lock_path))?;
+        file.lock().map_err(TaskError::io(&lock_path))?;
+        Ok(file) // unlocked when dropped
+    }
+
+    /// Writes `tasks` to the file; the caller holds the lock.
+    fn write(&mut self, tasks: &[Task]) -> Result<(), TaskError> {
+        let content = F::render(&Document::new(tasks.to_vec(), self.next_id));
+        write_atomic(&self.path, content.as_bytes()).map_err(TaskError::io(&self.path))?;
+        self.seen = Some(hash(content.as_bytes()));
+        self.tasks = tasks.to_vec();
+        Ok(())
+    }
+}
+
+impl<F: TextFormat> TaskStore for FileStore<F> {
+    fn load(&mut self) -> Result<Vec<Task>, TaskError> {
+        let _lock = self.lock()?;
+        let document = load_with_backup(&self.path, F::parse)?;
+        self.tasks = document.tasks;
+        self.next_id = document.next_id;
+        self.seen = fingerprint(&self.path)?;
+        Ok(self.tasks.clone())
+    }
+
+    fn save(&mut self, tasks: &[Task]) -> Result<(), TaskError> {
+        let _lock = self.lock()?;
+        if self.changed_on_disk()? {
+            return Err(TaskError::Conflict {
+                path: self.path.clone(),
+            });
+        }
+        self.write(tasks)
+    }
+
+    // Reads the counter and writes under the same lock, so a third writer
+    // can't slip in between and be overwritten unnoticed.
+    fn force_save(&mut self, tasks: &[Task]) -> Result<(), TaskError> {
+        let _lock = self.lock()?;
+        let document = load_with_backup(&self.path, F::parse)?;
+        self.next_id = self.next_id.max(document.next_id);
+        self.write(tasks)
+    }
+
+    // Only reserved in memory; the counter is written with the task using it.
+    fn next_id(&mut self) -> Result<usize, TaskError> {
+        let id = next_free_id(&self.tasks, self.next_id);
+        self.next_id = id + 1;
+        Ok(id)
+    }
+
+    // The single-task operations work on the list as last seen, never on a
+    // fresh read, so changes from other processes surface as conflicts.
+    fn append(&mut self, task: &Task) -> Result<(), TaskError> {
+        let mut tasks = self.tasks.clone();
+        tasks.push(task.clone());
+        self.save(&tasks)
+    }
+
+    fn update(&mut self, task: &Task) -> Result<(), TaskError> {
+        let mut tasks = self.tasks.clone();
+        if let Some(stored) = tasks.iter_mut().find(|t| t.get_id() == task.get_id()) {
+            *stored = task.clone();
+        }
+        self.save(&tasks)
+    }
+
+    fn delete(&mut self, id: usize) -> Result<(), TaskError> {
+        let mut tasks = self.tasks.clone();
+        tasks.retain(|task| task.get_id() != id);
+        self.save(&tasks)
+    }
+
+    fn reorder(&mut self, ids: &[usize]) -> Result<(), TaskError> {
+        let mut tasks = self.tasks.clone();
+        sort_by_ids(&mut tasks, ids);
+        self.save(&tasks)
+    }
+
+    fn changed_on_disk(&self) -> Result<bool, TaskError> {
+        Ok(fingerprint(&self.path)? != self.seen)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// Sibling of `path` holding the previous version of the file.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+fn hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn fingerprint(path: &Path) -> Result<Option<u64>, TaskError> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(hash(&content))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(TaskError::io(path)(err)),
+    }
+}
+
+/// Replaces the contents of `path` without ever leaving it half written.
+///
+/// The data goes to a temporary file in the same directory, is flushed to disk
+/// and then renamed over the original in a single step. The original is kept
+/// as the backup through a hard link (or a copy where links are not
+/// supported), so the data file never goes missing in between.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    let tmp_path = path.with_file_name(name);
+
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(contents)?;
+    tmp.sync_all()?;
+    drop(tmp);
+
+    if path.exists() {
+        let backup = backup_path(path);
+        match fs::remove_file(&backup) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        if fs::hard_link(path, &backup).is_err() {
+            fs::copy(path, &backup)?;
+        }
+    }
+    fs::rename(&tmp_path, path)?;
+    sync_parent_dir(path)
+}
+
+/// Makes the renames durable; directories can only be opened this way on unix.
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Parses `path`, falling back to its backup when the file is missing or
+/// cannot be parsed. A file from a newer schema is reported as is, since the
+/// backup would silently roll the data back.
+fn load_with_backup<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, TaskError>,
+) -> Result<T, TaskError> {
+    let read = |path: &Path| {
+        fs::read_to_string(path)
+            .map_err(TaskError::io(path))
+            .and_then(|content| parse(&content))
+    };
+    match read(path) {
+        Ok(value) => Ok(value),
+        Err(err @ TaskError::SchemaVersion { .. }) => Err(err),
+        Err(err) => {
+            let backup = backup_path(path);
+            if !backup.exists() {
+                return Err(err);
+            }
+            read(&backup).map_err(|_| err)
+        }
+    }
+}
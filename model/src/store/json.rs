@@ -0,0 +1,28 @@
+use super::{FileStore, TextFormat};
+use crate::common::TaskError;
+use crate::schema::{self, Document};
+use serde_json::Value;
+
+/// Stores the list as pretty-printed JSON inside a versioned `Document`.
+pub type JsonStore = FileStore<Json>;
+
+#[derive(Debug)]
+pub struct Json;
+
+impl TextFormat for Json {
+    fn parse(content: &str) -> Result<Document, TaskError> {
+        if content.trim().is_empty() {
+            return Ok(Document::new(Vec::new(), 0));
+        }
+        let value: Value = serde_json::from_str(content).map_err(|e| TaskError::Parse {
+            line: e.line(),
+            column: e.column(),
+            reason: e.to_string(),
+        })?;
+        schema::parse(value)
+    }
+
+    fn render(document: &Document) -> String {
+        serde_json::to_string_pretty(document).expect("tasks always serialize")
+    }
+}
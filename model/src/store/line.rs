@@ -0,0 +1,39 @@
+use super::{FileStore, TextFormat};
+use crate::common::TaskError;
+use crate::line;
+use crate::schema::Document;
+
+/// Stores one task per line in the format of `line::encode`, after a
+/// `# next_id N` header holding the id counter.
+pub type LineStore = FileStore<Lines>;
+
+#[derive(Debug)]
+pub struct Lines;
+
+const NEXT_ID: &str = "# next_id ";
+
+impl TextFormat for Lines {
+    fn parse(content: &str) -> Result<Document, TaskError> {
+        let mut next_id = 0;
+        let mut tasks = Vec::new();
+        for (i, record) in content.lines().enumerate() {
+            if let Some(id) = record.strip_prefix(NEXT_ID) {
+                next_id = id.trim().parse().map_err(|_| TaskError::Parse {
+                    line: i + 1,
+                    column: NEXT_ID.len() + 1,
+                    reason: format!("`{}` is not an id", id),
+                })?;
+            } else if !record.is_empty() && !record.starts_with('#') {
+                tasks.push(line::decode(record, i + 1)?);
+            }
+        }
+        // files written before the header fall back to the highest id
+        Ok(Document::new(tasks, next_id))
+    }
+
+    fn render(document: &Document) -> String {
+        let header = format!("{}{}\n", NEXT_ID, document.next_id);
+        let records = document.tasks.iter().map(|task| line::encode(task) + "\n");
+        std::iter::once(header).chain(records).collect()
+    }
+}
@@ -0,0 +1,55 @@
+use super::{sort_by_ids, TaskStore};
+use crate::common::{Task, TaskError};
+use crate::schema::next_free_id;
+
+/// Keeps the tasks in memory only. Mostly useful for tests.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tasks: Vec<Task>,
+    next_id: usize,
+}
+
+impl MemoryStore {
+    pub fn new(tasks: Vec<Task>) -> MemoryStore {
+        MemoryStore { tasks, next_id: 0 }
+    }
+}
+
+impl TaskStore for MemoryStore {
+    fn load(&mut self) -> Result<Vec<Task>, TaskError> {
+        Ok(self.tasks.clone())
+    }
+
+    fn save(&mut self, tasks: &[Task]) -> Result<(), TaskError> {
+        self.tasks = tasks.to_vec();
+        Ok(())
+    }
+
+    fn next_id(&mut self) -> Result<usize, TaskError> {
+        let id = next_free_id(&self.tasks, self.next_id);
+        self.next_id = id + 1;
+        Ok(id)
+    }
+
+    fn append(&mut self, task: &Task) -> Result<(), TaskError> {
+        self.tasks.push(task.clone());
+        Ok(())
+    }
+
+    fn update(&mut self, task: &Task) -> Result<(), TaskError> {
+        if let Some(stored) = self.tasks.iter_mut().find(|t| t.get_id() == task.get_id()) {
+            *stored = task.clone();
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, id: usize) -> Result<(), TaskError> {
+        self.tasks.retain(|task| task.get_id() != id);
+        Ok(())
+    }
+
+    fn reorder(&mut self, ids: &[usize]) -> Result<(), TaskError> {
+        sort_by_ids(&mut self.tasks, ids);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::common::{Task, TaskError};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+mod file;
+mod json;
+mod line;
+mod memory;
+mod sqlite;
+
+pub(crate) use file::write_atomic;
+pub use file::{backup_path, FileStore, TextFormat};
+pub use json::{Json, JsonStore};
+pub use line::{LineStore, Lines};
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+/// Backing storage for the task list.
+///
+/// Only `load`, `save` and `next_id` are required; the remaining operations
+/// fall back to a full load/save round trip and backends override them when
+/// they can do better (e.g. updating a single row).
+pub trait TaskStore: fmt::Debug {
+    fn load(&mut self) -> Result<Vec<Task>, TaskError>;
+
+    fn save(&mut self, tasks: &[Task]) -> Result<(), TaskError>;
+
+    /// Reserves an id for a new task. Ids only ever go up, so one is never
+    /// handed out again after its task is removed.
+    fn next_id(&mut self) -> Result<usize, TaskError>;
+
+    fn append(&mut self, task: &Task) -> Result<(), TaskError> {
+        let mut tasks = self.load()?;
+        tasks.push(task.clone());
+        self.save(&tasks)
+    }
+
+    /// Writes back a single task that changed in place.
+    fn update(&mut self, task: &Task) -> Result<(), TaskError> {
+        let mut tasks = self.load()?;
+        if let Some(stored) = tasks.iter_mut().find(|t| t.get_id() == task.get_id()) {
+            *stored = task.clone();
+        }
+        self.save(&tasks)
+    }
+
+    fn delete(&mut self, id: usize) -> Result<(), TaskError> {
+        let mut tasks = self.load()?;
+        tasks.retain(|task| task.get_id() != id);
+        self.save(&tasks)
+    }
+
+    fn clear(&mut self) -> Result<(), TaskError> {
+        self.save(&[])
+    }
+
+    /// Stores the list order given by `ids`, first to last, without
+    /// rewriting the tasks themselves.
+    fn reorder(&mut self, ids: &[usize]) -> Result<(), TaskError> {
+        let mut tasks = self.load()?;
+        sort_by_ids(&mut tasks, ids);
+        self.save(&tasks)
+    }
+
+    /// Saves `tasks` even though the backing file changed underneath,
+    /// replacing whatever another process wrote. Ids handed out there stay
+    /// retired.
+    fn force_save(&mut self, tasks: &[Task]) -> Result<(), TaskError> {
+        self.load()?;
+        self.save(tasks)
+    }
+
+    /// Whether the backing file differs from what this store last read or
+    /// wrote, i.e. someone else changed it.
+    fn changed_on_disk(&self) -> Result<bool, TaskError> {
+        Ok(false)
+    }
+
+    /// File the store reads from, if it is backed by one.
+    fn path(&self) -> Option<&Path> {
+        None
+    }
+}
+
+// Lets `App` keep deriving `Default`; an app without a configured store works
+// purely in memory.
+impl Default for Box<dyn TaskStore> {
+    fn default() -> Self {
+        Box::new(MemoryStore::default())
+    }
+}
+
+/// Puts `tasks` in the order of `ids`; tasks missing from it go last.
+pub(crate) fn sort_by_ids(tasks: &mut [Task], ids: &[usize]) {
+    let order: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    tasks.sort_by_key(|task| order.get(&task.get_id()).copied().unwrap_or(ids.len()));
+}
+
+pub(crate) fn create_parent_dir(path: &Path) -> Result<(), TaskError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            std::fs::create_dir_all(parent).map_err(TaskError::io(parent))
+        }
+        _ => Ok(()),
+    }
+}
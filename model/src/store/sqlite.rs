@@ -0,0 +1,238 @@
+use super::{create_parent_dir, TaskStore};
+use crate::common::{Task, TaskError};
+use crate::schema::{self, SCHEMA_VERSION};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File extensions that select the SQLite backend.
+pub const SQLITE_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// Stores every task as its own row, so single-task changes only touch that row.
+///
+/// The task itself is kept as JSON in the `data` column; `id` is the primary
+/// key and `position` keeps the list order. The schema version of the rows is
+/// tracked in `PRAGMA user_version` and they are migrated on load. The id
+/// counter lives in the `meta` table under `next_id`.
+///
+/// `PRAGMA data_version` as of the last load tells whether another
+/// connection committed since; a full `save` is then refused with
+/// `TaskError::Conflict`. Single-row changes only touch their own row and
+/// go through regardless.
+#[derive(Debug)]
+pub struct SqliteStore {
+    path: PathBuf,
+    conn: Connection,
+    seen: i64,
+}
+
+impl SqliteStore {
+    pub fn new(path: PathBuf) -> Result<SqliteStore, TaskError> {
+        create_parent_dir(&path)?;
+        let conn = Connection::open(&path).map_err(db_error(&path))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                id INTEGER PRIMARY KEY,
+                position INTEGER NOT NULL,
+                data TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS tasks_position ON tasks (position);
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value INTEGER NOT NULL
+            );",
+        )
+        .map_err(db_error(&path))?;
+        let seen = data_version(&conn).map_err(db_error(&path))?;
+        Ok(SqliteStore { path, conn, seen })
+    }
+
+    pub fn is_sqlite_path(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| SQLITE_EXTENSIONS.contains(&ext))
+    }
+
+    pub fn is_empty(&self) -> Result<bool, TaskError> {
+        let row: Option<i64> = self
+            .conn
+            .query_row("SELECT id FROM tasks LIMIT 1", [], |row| row.get(0))
+            .optional()
+            .map_err(db_error(&self.path))?;
+        Ok(row.is_none())
+    }
+
+    /// Copies `tasks` into an empty database, returning how many were imported.
+    pub fn import(&mut self, tasks: &[Task]) -> Result<usize, TaskError> {
+        if !self.is_empty()? {
+            return Ok(0);
+        }
+        self.save(tasks)?;
+        Ok(tasks.len())
+    }
+}
+
+/// Lowest id above both the stored counter and every id in `tasks`.
+fn next_free_id(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.query_row(
+        "SELECT MAX(
+            COALESCE((SELECT value FROM meta WHERE key = 'next_id'), 0),
+            COALESCE((SELECT MAX(id) FROM tasks), -1) + 1
+        )",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|id| id as usize)
+}
+
+fn set_next_id(conn: &Connection, id: usize) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES ('next_id', ?1)",
+        params![id as i64],
+    )
+}
+
+/// Changes whenever another connection commits to the database.
+fn data_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.pragma_query_value(None, "data_version", |row| row.get(0))
+}
+
+fn to_data(task: &Task) -> String {
+    serde_json::to_string(task).expect("tasks always serialize")
+}
+
+fn db_error(path: &Path) -> impl FnOnce(rusqlite::Error) -> TaskError + '_ {
+    move |err| TaskError::io(path)(io::Error::other(err))
+}
+
+impl TaskStore for SqliteStore {
+    fn load(&mut self) -> Result<Vec<Task>, TaskError> {
+        let version: u64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(db_error(&self.path))?;
+        self.seen = data_version(&self.conn).map_err(db_error(&self.path))?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM tasks ORDER BY position, id")
+            .map_err(db_error(&self.path))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_error(&self.path))?;
+        let mut values = Vec::new();
+        for data in rows {
+            let data = data.map_err(db_error(&self.path))?;
+            let value = serde_json::from_str::<Value>(&data).map_err(|e| TaskError::Parse {
+                line: e.line(),
+                column: e.column(),
+                reason: format!("stored task: {}", e),
+            })?;
+            values.push(value);
+        }
+        drop(stmt);
+
+        // rows from before versioning are shaped like a bare version 0 array
+        let next_id = next_free_id(&self.conn).map_err(db_error(&self.path))?;
+        let document = match version {
+            0 => Value::Array(values),
+            _ => json!({ "schema_version": version, "next_id": next_id, "tasks": values }),
+        };
+        let tasks = schema::parse(document)?.tasks;
+        if version != SCHEMA_VERSION {
+            self.save(&tasks)?;
+        }
+        Ok(tasks)
+    }
+
+    fn save(&mut self, tasks: &[Task]) -> Result<(), TaskError> {
+        if self.changed_on_disk()? {
+            return Err(TaskError::Conflict {
+                path: self.path.clone(),
+            });
+        }
+        let tx = self.conn.transaction().map_err(db_error(&self.path))?;
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(db_error(&self.path))?;
+        tx.execute("DELETE FROM tasks", [])
+            .map_err(db_error(&self.path))?;
+        for (position, task) in tasks.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO tasks (id, position, data) VALUES (?1, ?2, ?3)",
+                params![task.get_id() as i64, position as i64, to_data(task)],
+            )
+            .map_err(db_error(&self.path))?;
+        }
+        next_free_id(&tx)
+            .and_then(|id| set_next_id(&tx, id))
+            .map_err(db_error(&self.path))?;
+        tx.commit().map_err(db_error(&self.path))
+    }
+
+    fn next_id(&mut self) -> Result<usize, TaskError> {
+        let tx = self.conn.transaction().map_err(db_error(&self.path))?;
+        let id = next_free_id(&tx).map_err(db_error(&self.path))?;
+        set_next_id(&tx, id + 1).map_err(db_error(&self.path))?;
+        tx.commit().map_err(db_error(&self.path))?;
+        Ok(id)
+    }
+
+    fn append(&mut self, task: &Task) -> Result<(), TaskError> {
+        self.conn
+            .execute(
+                "INSERT INTO tasks (id, position, data)
+                 VALUES (?1, (SELECT COALESCE(MAX(position), -1) + 1 FROM tasks), ?2)",
+                params![task.get_id() as i64, to_data(task)],
+            )
+            .map_err(db_error(&self.path))?;
+        Ok(())
+    }
+
+    fn update(&mut self, task: &Task) -> Result<(), TaskError> {
+        self.conn
+            .execute(
+                "UPDATE tasks SET data = ?2 WHERE id = ?1",
+                params![task.get_id() as i64, to_data(task)],
+            )
+            .map_err(db_error(&self.path))?;
+        Ok(())
+    }
+
+    fn delete(&mut self, id: usize) -> Result<(), TaskError> {
+        self.conn
+            .execute("DELETE FROM tasks WHERE id = ?1", params![id as i64])
+            .map_err(db_error(&self.path))?;
+        Ok(())
+    }
+
+    // Only the `position` column changes, and only on rows that moved; tasks
+    // other connections added keep theirs.
+    fn reorder(&mut self, ids: &[usize]) -> Result<(), TaskError> {
+        let tx = self.conn.transaction().map_err(db_error(&self.path))?;
+        for (position, &id) in ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE tasks SET position = ?2 WHERE id = ?1 AND position != ?2",
+                params![id as i64, position as i64],
+            )
+            .map_err(db_error(&self.path))?;
+        }
+        tx.commit().map_err(db_error(&self.path))
+    }
+
+    fn clear(&mut self) -> Result<(), TaskError> {
+        self.conn
+            .execute("DELETE FROM tasks", [])
+            .map_err(db_error(&self.path))?;
+        Ok(())
+    }
+
+    fn changed_on_disk(&self) -> Result<bool, TaskError> {
+        let version = data_version(&self.conn).map_err(db_error(&self.path))?;
+        Ok(version != self.seen)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::common::Task;
+use std::collections::BTreeSet;
+
+/// A description split into its text and the `+project`, `@context` and
+/// `#tag` tokens typed into it.
+#[derive(Debug, Default, PartialEq)]
+pub struct Tokens {
+    pub description: String,
+    /// Last `+project` token, if any.
+    pub project: Option<String>,
+    /// Names of the `@context` and `#tag` tokens, without the sigil. Both
+    /// kinds end up in the same set.
+    pub tags: BTreeSet<String>,
+}
+
+/// Pulls the tokens out of `input`. A token is a whole word starting with
+/// `+`, `@` or `#` followed by a name; anything else stays in the
+/// description. When tokens are found the remaining words are joined with
+/// single spaces.
+pub fn parse_tokens(input: &str) -> Tokens {
+    let mut tokens = Tokens::default();
+    let mut words = Vec::new();
+    for word in input.split_whitespace() {
+        match split_sigil(word) {
+            Some(('+', name)) => tokens.project = Some(name.to_owned()),
+            Some((_, name)) => {
+                tokens.tags.insert(name.to_owned());
+            }
+            None => words.push(word),
+        }
+    }
+    tokens.description = if tokens.project.is_none() && tokens.tags.is_empty() {
+        input.to_owned()
+    } else {
+        words.join(" ")
+    };
+    tokens
+}
+
+/// The description of `task` followed by its project and tags written as
+/// tokens, so that `parse_tokens` gives them back.
+pub fn with_tokens(task: &Task) -> String {
+    let mut text = task.description();
+    if let Some(project) = task.project() {
+        text.push_str(&format!(" +{}", project));
+    }
+    for tag in task.tags() {
+        text.push_str(&format!(" #{}", tag));
+    }
+    text
+}
+
+fn split_sigil(word: &str) -> Option<(char, &str)> {
+    let sigil = word.chars().next()?;
+    let name = &word[sigil.len_utf8()..];
+    let valid = matches!(sigil, '+' | '@' | '#')
+        && name
+            .chars()
+            .next()
+            .is_some_and(|c| !matches!(c, '+' | '@' | '#'));
+    valid.then_some((sigil, name))
+}
@@ -0,0 +1,162 @@
+use crate::common::{Task, TaskError};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A stretch of time spent on a task. `end` is `None` while the timer runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    /// Time covered by the entry, counting a running one up to `now`.
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        (self.end.unwrap_or(now) - self.start).max(Duration::zero())
+    }
+
+    /// The entry cut at local midnights, as the time it covers on each day.
+    fn per_day(&self, now: DateTime<Local>) -> Vec<(NaiveDate, Duration)> {
+        let end = self.end.unwrap_or(now);
+        let mut days = Vec::new();
+        let mut from = self.start;
+        while from < end {
+            let day = from.date_naive();
+            let midnight = day
+                .succ_opt()
+                .and_then(|next| next.and_hms_opt(0, 0, 0))
+                .and_then(|next| next.and_local_timezone(Local).earliest())
+                .filter(|&next| next > from)
+                .unwrap_or(end);
+            let to = midnight.min(end);
+            days.push((day, to - from));
+            from = to;
+        }
+        days
+    }
+}
+
+/// `start/end` in RFC 3339, like an ISO 8601 interval. A running entry has
+/// nothing after the slash.
+impl fmt::Display for TimeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = self.end.map(|end| end.to_rfc3339()).unwrap_or_default();
+        write!(f, "{}/{}", self.start.to_rfc3339(), end)
+    }
+}
+
+impl FromStr for TimeEntry {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<TimeEntry, TaskError> {
+        let invalid = || TaskError::Validation {
+            field: "time_entries",
+            reason: format!("`{}` is not a start/end pair", s),
+        };
+        let (start, end) = s.split_once('/').ok_or_else(invalid)?;
+        Ok(TimeEntry {
+            start: start.parse().map_err(|_| invalid())?,
+            end: match end {
+                "" => None,
+                end => Some(end.parse().map_err(|_| invalid())?),
+            },
+        })
+    }
+}
+
+/// What the tracked time is added up by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportBy {
+    #[default]
+    Task,
+    Tag,
+    Day,
+}
+
+impl ReportBy {
+    pub const ALL: [ReportBy; 3] = [ReportBy::Task, ReportBy::Tag, ReportBy::Day];
+}
+
+impl fmt::Display for ReportBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReportBy::Task => "task",
+            ReportBy::Tag => "tag",
+            ReportBy::Day => "day",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ReportBy {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<ReportBy, TaskError> {
+        ReportBy::ALL
+            .into_iter()
+            .find(|by| by.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| TaskError::Validation {
+                field: "report",
+                reason: format!("unknown `{}`, expected task, tag or day", s),
+            })
+    }
+}
+
+/// Tracked time per task (`id description`), per `#tag` or per day, only
+/// counting the days from `since` to `until`. Time on a task with several
+/// tags counts toward each of them; untagged time is listed as `untagged`.
+/// Rows are in task order, by tag name or by day, and never zero.
+pub fn report(
+    tasks: &[Task],
+    by: ReportBy,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    now: DateTime<Local>,
+) -> Vec<(String, Duration)> {
+    let mut rows: BTreeMap<(usize, String), Duration> = BTreeMap::new();
+    for (position, task) in tasks.iter().enumerate() {
+        let days = task
+            .time_entries()
+            .iter()
+            .flat_map(|entry| entry.per_day(now))
+            .filter(|&(day, _)| since.is_none_or(|since| day >= since))
+            .filter(|&(day, _)| until.is_none_or(|until| day <= until));
+        for (day, spent) in days {
+            let keys = match by {
+                ReportBy::Task => vec![(
+                    position,
+                    format!("{} {}", task.get_id(), task.description()),
+                )],
+                ReportBy::Tag if task.tags().is_empty() => vec![(0, String::from("untagged"))],
+                ReportBy::Tag => task
+                    .tags()
+                    .iter()
+                    .map(|tag| (0, format!("#{}", tag)))
+                    .collect(),
+                ReportBy::Day => vec![(0, day.to_string())],
+            };
+            for key in keys {
+                *rows.entry(key).or_insert_with(Duration::zero) += spent;
+            }
+        }
+    }
+    rows.into_iter()
+        .filter(|(_, spent)| *spent > Duration::zero())
+        .map(|((_, key), spent)| (key, spent))
+        .collect()
+}
+
+/// `h:mm:ss`, the hours growing as needed.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
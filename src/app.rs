@@ -1,11 +1,35 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use model::{common::Task, util::is_completed};
-use ratatui::{prelude::Backend, widgets::ListState, Terminal};
+use chrono::{DateTime, Local, NaiveDateTime};
+use clap::ValueEnum;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+use model::{
+    common::{Stamp, Task, TaskError},
+    config::{Config, SavedView},
+    deps::find_cycle,
+    due::{parse_due, Due},
+    line::blocked_by,
+    priority::Priority,
+    query::Query,
+    recur::{next_occurrence, parse_recurrence, Recurrence},
+    search::{search_task, SearchHit},
+    status::Status,
+    store::{JsonStore, LineStore, SqliteStore, TaskStore},
+    tags::{parse_tokens, with_tokens},
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use ratatui::{
+    layout::{Margin, Position, Rect},
+    prelude::Backend,
+    widgets::ListState,
+    Terminal,
+};
 use std::{
-    error::Error,
-    fs::{self, create_dir_all, File},
-    io::{BufRead, BufReader, Read, Write},
-    path::{Path, PathBuf},
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+    sync::mpsc,
 };
 
 use crate::ui::render;
@@ -15,6 +39,33 @@ pub enum CurrentEditing {
     #[default]
     Description,
     Body,
+    Due,
+    BlockedBy,
+    Recurrence,
+}
+
+impl CurrentEditing {
+    /// Field that `Tab` moves to.
+    pub fn next(&self) -> CurrentEditing {
+        match self {
+            CurrentEditing::Description => CurrentEditing::Body,
+            CurrentEditing::Body => CurrentEditing::Due,
+            CurrentEditing::Due => CurrentEditing::BlockedBy,
+            CurrentEditing::BlockedBy => CurrentEditing::Recurrence,
+            CurrentEditing::Recurrence => CurrentEditing::Description,
+        }
+    }
+
+    /// Name shown on the editor popup.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CurrentEditing::Description => "Description",
+            CurrentEditing::Body => "Body",
+            CurrentEditing::Due => "Due",
+            CurrentEditing::BlockedBy => "Blocked by",
+            CurrentEditing::Recurrence => "Repeat",
+        }
+    }
 }
 
 #[derive(Debug, Default, PartialEq)]
@@ -25,6 +76,328 @@ pub enum CurrentScreen {
     Deleting,
     Help,
     Exiting,
+    Conflict,
+    Picker,
+    Search,
+    Query,
+}
+
+/// Order tasks are listed in. Sorting only changes the view; the stored
+/// order is left alone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SortMode {
+    /// Stored order, as the tasks were added or arranged
+    #[default]
+    #[value(alias = "position")]
+    Manual,
+    /// Most important first, then earliest due date
+    Priority,
+    /// Earliest due date first, then tasks without one
+    Due,
+    /// Newest first
+    Created,
+    /// Most recently changed first
+    Updated,
+    /// Most recently completed first, then open tasks
+    Completed,
+    /// By description, A to Z
+    Alphabetical,
+    /// Along the workflow, from todo to cancelled
+    Status,
+}
+
+impl SortMode {
+    /// Mode that `S` switches to.
+    pub fn next(&self) -> SortMode {
+        match self {
+            SortMode::Manual => SortMode::Priority,
+            SortMode::Priority => SortMode::Due,
+            SortMode::Due => SortMode::Created,
+            SortMode::Created => SortMode::Updated,
+            SortMode::Updated => SortMode::Completed,
+            SortMode::Completed => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::Status,
+            SortMode::Status => SortMode::Manual,
+        }
+    }
+
+    /// Timestamp the mode sorts by, if any.
+    pub fn stamp(&self) -> Option<Stamp> {
+        match self {
+            SortMode::Created => Some(Stamp::Created),
+            SortMode::Updated => Some(Stamp::Updated),
+            SortMode::Completed => Some(Stamp::Completed),
+            _ => None,
+        }
+    }
+
+    /// What the list is sorted by, for the list title.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual order",
+            SortMode::Priority => "priority",
+            SortMode::Due => "due date",
+            SortMode::Created => "created",
+            SortMode::Updated => "updated",
+            SortMode::Completed => "completed",
+            SortMode::Alphabetical => "description",
+            SortMode::Status => "status",
+        }
+    }
+
+    /// Whether the mode lists the highest or latest values first unless
+    /// reversed.
+    pub fn descending(&self) -> bool {
+        matches!(
+            self,
+            SortMode::Priority | SortMode::Created | SortMode::Updated | SortMode::Completed
+        )
+    }
+
+    /// Orders two tasks the way the mode lists them, or the other way round
+    /// when `reversed`. Tasks without a due date or timestamp stay last
+    /// either way; the manual order is reversed by the caller.
+    pub fn compare(&self, a: &Task, b: &Task, reversed: bool) -> Ordering {
+        let flip = |ordering: Ordering| {
+            if reversed {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+        let deadline = |task: &Task| task.due().map(|due| due.deadline());
+        match self {
+            SortMode::Manual => Ordering::Equal,
+            SortMode::Priority => flip(b.priority().cmp(&a.priority()))
+                .then_with(|| missing_last(deadline(a), deadline(b), false)),
+            SortMode::Due => missing_last(deadline(a), deadline(b), reversed),
+            SortMode::Created | SortMode::Updated | SortMode::Completed => {
+                let stamp = self.stamp().unwrap_or(Stamp::Created);
+                missing_last(a.stamp(stamp), b.stamp(stamp), !reversed)
+            }
+            SortMode::Alphabetical => flip(
+                a.description()
+                    .to_lowercase()
+                    .cmp(&b.description().to_lowercase()),
+            ),
+            SortMode::Status => flip(a.status().cmp(&b.status())),
+        }
+    }
+}
+
+/// Compares two optional values, putting `None` after any value.
+fn missing_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// Tags, project, period and query the Main view is narrowed to.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Filter {
+    pub tags: BTreeSet<String>,
+    pub project: Option<String>,
+    /// Only tasks with the timestamp within this many days, today included.
+    pub recent: Option<(Stamp, u32)>,
+    /// Query typed after `:`.
+    pub query: Option<Query>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.project.is_none()
+            && self.recent.is_none()
+            && self.query.is_none()
+    }
+
+    /// A task matches when it has any of the chosen tags, belongs to the
+    /// chosen project, falls in the chosen period and matches the query. An
+    /// empty filter matches everything.
+    pub fn matches(&self, task: &Task) -> bool {
+        let project = match &self.project {
+            Some(project) => task.project() == Some(project.as_str()),
+            None => true,
+        };
+        let recent = match self.recent {
+            Some((stamp, days)) => {
+                let today = Local::now().date_naive();
+                let since = today - chrono::Days::new(u64::from(days.saturating_sub(1)));
+                task.stamp(stamp).is_some_and(|at| at.date_naive() >= since)
+            }
+            None => true,
+        };
+        let query = self.query.as_ref().is_none_or(|query| query.matches(task));
+        project && recent && query && (self.tags.is_empty() || !self.tags.is_disjoint(task.tags()))
+    }
+
+    /// Whether completed tasks are listed even while hidden, because the
+    /// query asks for them.
+    pub fn shows_closed(&self) -> bool {
+        self.query.as_ref().is_some_and(Query::shows_closed)
+    }
+
+    /// Adds the entry to the filter, or removes it when already there. Only
+    /// one project can be chosen at a time.
+    pub fn toggle(&mut self, entry: &PickerEntry) {
+        match entry {
+            PickerEntry::Project(name) if self.project.as_ref() == Some(name) => {
+                self.project = None
+            }
+            PickerEntry::Project(name) => self.project = Some(name.clone()),
+            PickerEntry::Tag(name) => {
+                if !self.tags.remove(name) {
+                    self.tags.insert(name.clone());
+                }
+            }
+            PickerEntry::Recent(stamp, days) if self.recent == Some((*stamp, *days)) => {
+                self.recent = None
+            }
+            PickerEntry::Recent(stamp, days) => self.recent = Some((*stamp, *days)),
+        }
+    }
+
+    pub fn contains(&self, entry: &PickerEntry) -> bool {
+        match entry {
+            PickerEntry::Project(name) => self.project.as_ref() == Some(name),
+            PickerEntry::Tag(name) => self.tags.contains(name),
+            PickerEntry::Recent(stamp, days) => self.recent == Some((*stamp, *days)),
+        }
+    }
+}
+
+/// Row of the filter picker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickerEntry {
+    Project(String),
+    Tag(String),
+    /// Timestamp within the last number of days.
+    Recent(Stamp, u32),
+}
+
+/// Row of the views sidebar. The active view narrows the list on top of the
+/// filter.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum View {
+    #[default]
+    All,
+    /// Open tasks without a project.
+    Inbox,
+    /// Tasks due today.
+    Today,
+    /// Open tasks past their due date.
+    Overdue,
+    Project(String),
+    /// Query saved in the config under a name.
+    Saved(String, Query),
+}
+
+impl View {
+    pub fn label(&self) -> String {
+        match self {
+            View::All => String::from("All"),
+            View::Inbox => String::from("Inbox"),
+            View::Today => String::from("Today"),
+            View::Overdue => String::from("Overdue"),
+            View::Project(name) => format!("+{}", name),
+            View::Saved(name, _) => name.clone(),
+        }
+    }
+
+    pub fn matches(&self, task: &Task, now: NaiveDateTime) -> bool {
+        match self {
+            View::All => true,
+            View::Inbox => task.project().is_none() && !task.completed(),
+            View::Today => task.due().is_some_and(|due| due.date() == now.date()),
+            View::Overdue => task.due().is_some_and(|due| due.is_overdue(now)) && !task.completed(),
+            View::Project(name) => task.project() == Some(name.as_str()),
+            View::Saved(_, query) => query.matches(task),
+        }
+    }
+
+    /// Whether completed tasks are listed even while hidden.
+    pub fn shows_closed(&self) -> bool {
+        match self {
+            View::Saved(_, query) => query.shows_closed(),
+            _ => false,
+        }
+    }
+}
+
+/// Id and parent lookups over a task list, built in one pass so asking for
+/// the subtasks or blockers of every listed task stays linear.
+#[derive(Debug)]
+pub struct Links<'a> {
+    tasks: &'a [Task],
+    positions: HashMap<usize, usize>,
+    children: HashMap<usize, Vec<usize>>,
+}
+
+impl<'a> Links<'a> {
+    pub fn new(tasks: &'a [Task]) -> Links<'a> {
+        let mut positions = HashMap::with_capacity(tasks.len());
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, task) in tasks.iter().enumerate() {
+            positions.insert(task.get_id(), i);
+            if let Some(parent) = task.parent() {
+                children.entry(parent).or_default().push(i);
+            }
+        }
+        Links {
+            tasks,
+            positions,
+            children,
+        }
+    }
+
+    /// Index of the task with the given id.
+    pub fn position(&self, id: usize) -> Option<usize> {
+        self.positions.get(&id).copied()
+    }
+
+    /// Indices of the direct subtasks of the task with `id`.
+    pub fn children(&self, id: usize) -> &[usize] {
+        self.children.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Indices of every task below the task with `id`.
+    pub fn descendants(&self, id: usize) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            for &child in self.children(id) {
+                if seen.insert(child) {
+                    found.push(child);
+                    pending.push(self.tasks[child].get_id());
+                }
+            }
+        }
+        found
+    }
+
+    /// Completed and total direct subtasks of the task at `index`, or `None`
+    /// when it has none.
+    pub fn progress(&self, index: usize) -> Option<(usize, usize)> {
+        let children = self.children(self.tasks[index].get_id());
+        let done = children
+            .iter()
+            .filter(|&&i| self.tasks[i].completed())
+            .count();
+        (!children.is_empty()).then_some((done, children.len()))
+    }
+
+    /// Indices of the unfinished tasks the task at `index` waits on.
+    pub fn blockers(&self, index: usize) -> Vec<usize> {
+        self.tasks[index]
+            .blocked_by()
+            .iter()
+            .filter_map(|&id| self.position(id))
+            .filter(|&i| !self.tasks[i].completed())
+            .collect()
+    }
 }
 
 #[derive(Debug, Default)]
@@ -34,80 +407,93 @@ pub struct App {
     pub current_editing: CurrentEditing,
     pub quit: bool,
     pub show_done: bool, // TODO: better data structure
+    pub sort: SortMode,
+    /// Whether the list runs against the usual direction of the sort.
+    pub sort_reversed: bool,
+    pub filter: Filter,
+    /// Sidebar view the list is narrowed to.
+    pub active_view: View,
+    pub picker: ListState,
+    /// Ids of the tasks whose subtasks are folded away.
+    pub collapsed: HashSet<usize>,
+    /// Whether completing a task also completes its subtasks.
+    pub cascade: bool,
+    pub config: Config,
+    /// Where saved views are written back to; `None` keeps them in memory.
+    pub config_path: Option<PathBuf>,
+    /// Time of the last tick; running timers are shown counted up to it.
+    pub clock: DateTime<Local>,
+    /// Text typed after `/`. Matching tasks are highlighted, not filtered.
+    pub search: String,
+    /// Task selected when the search was opened; typing jumps from there.
+    search_origin: Option<usize>,
     pub loading: bool,
     pub state: ListState,
+    /// Where the task list was last drawn, to tell which row the mouse is on.
+    pub list_area: Rect,
+    /// Id of the task being dragged with the mouse, and whether it moved.
+    drag: Option<(usize, bool)>,
     pub throbber_state: throbber_widgets_tui::ThrobberState,
-    pub path: PathBuf,
-    pub path_bin: PathBuf,
-    pub with_json: bool,
+    pub store: Box<dyn TaskStore>,
     pub buffer: String,
     pub editing: bool,
     pub character_index: usize,
+    pub status: Option<String>,
+    pub watcher: Option<RecommendedWatcher>,
+    pub file_events: Option<mpsc::Receiver<notify::Result<notify::Event>>>,
+    pub file_changed: bool,
 }
 
 impl App {
-    pub fn new(path: PathBuf) -> Result<App, Box<dyn Error>> {
-        if let Some(parent) = path.parent() {
-            if let Err(e) = create_dir_all(parent) {
-                eprintln!("Error creating directory {:?}: {}", parent, e);
-                std::process::exit(1);
-            }
-        }
-
-        let mut user_data = File::options()
-            .read(true)
-            .append(true)
-            .create(true)
-            .open(&path)?;
+    pub fn new(path: PathBuf) -> Result<App, TaskError> {
+        Self::with_store(Box::new(LineStore::new(path)?))
+    }
 
-        let mut buf = String::new();
-        user_data.read_to_string(&mut buf)?;
+    pub fn with_json(path: PathBuf) -> Result<App, TaskError> {
+        Self::with_store(Box::new(JsonStore::new(path)?))
+    }
 
-        let tasks = if buf.is_empty() {
-            Vec::new()
-        } else {
-            buf.lines().map(Task::from_line).collect::<Result<_, _>>()?
-        };
+    /// Opens a SQLite database, importing the JSON file next to it (same name,
+    /// `.json` extension) the first time the database is empty.
+    pub fn with_sqlite(path: PathBuf) -> Result<App, TaskError> {
+        let json_path = path.with_extension("json");
+        let mut store = SqliteStore::new(path)?;
+        if json_path.exists() && store.is_empty()? {
+            let tasks = JsonStore::new(json_path)?.load()?;
+            store.import(&tasks)?;
+        }
+        Self::with_store(Box::new(store))
+    }
 
-        Ok(App {
+    pub fn with_store(mut store: Box<dyn TaskStore>) -> Result<App, TaskError> {
+        let tasks = store.load()?;
+        let mut app = App {
             tasks,
-            state: ListState::default().with_selected(Some(0)),
-            path_bin: path,
+            store,
+            clock: Local::now(),
             ..Default::default()
-        })
+        };
+        app.select_id(None);
+        Ok(app)
     }
 
-    pub fn with_json(path: PathBuf) -> Result<App, Box<dyn Error>> {
-        let file = Path::new(path.as_path());
-
-        if let Some(parent) = path.parent() {
-            if let Err(e) = create_dir_all(parent) {
-                eprintln!("Error creating directory {:?}: {}", parent, e);
-                std::process::exit(1);
-            }
-        }
+    /// Watches the data file so changes made by other processes show up
+    /// without restarting. The parent directory is watched because saves
+    /// replace the file through a rename.
+    pub fn watch(&mut self) -> notify::Result<()> {
+        let Some(path) = self.store.path().map(|path| path.to_path_buf()) else {
+            return Ok(());
+        };
+        let Some(dir) = path.parent() else {
+            return Ok(());
+        };
 
-        match !file.exists() {
-            true => {
-                fs::File::create(path.clone())?;
-                Ok(App {
-                    tasks: Vec::new(),
-                    path: path.to_owned(),
-                    with_json: true,
-                    ..Default::default()
-                })
-            }
-            false => {
-                let buf = Self::read_from_json(path.clone())?;
-                Ok(App {
-                    tasks: buf,
-                    path: path.to_owned(),
-                    with_json: true,
-                    state: ListState::default().with_selected(Some(0)),
-                    ..Default::default()
-                })
-            }
-        }
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        self.watcher = Some(watcher);
+        self.file_events = Some(rx);
+        Ok(())
     }
 
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> color_eyre::Result<()> {
@@ -131,13 +517,40 @@ impl App {
 
     fn on_tick(&mut self) {
         self.throbber_state.calc_next();
+        self.clock = Local::now();
+        self.check_file_events();
+    }
+
+    fn check_file_events(&mut self) {
+        if let (Some(events), Some(path)) = (&self.file_events, self.store.path()) {
+            let file_name = path.file_name();
+            self.file_changed |= events
+                .try_iter()
+                .filter_map(Result::ok)
+                .any(|event| event.paths.iter().any(|p| p.file_name() == file_name));
+        }
+
+        // wait until nothing is being edited before swapping the list out
+        if !self.file_changed || self.current_screen != CurrentScreen::Main || self.drag.is_some() {
+            return;
+        }
+        self.file_changed = false;
+
+        match self.store.changed_on_disk() {
+            Ok(false) => {}
+            Ok(true) => match self.reload() {
+                Ok(()) => self.status = Some(String::from("Reloaded tasks from disk")),
+                Err(err) => self.status = Some(format!("Could not reload tasks: {}", err)),
+            },
+            Err(err) => self.status = Some(format!("Could not read tasks: {}", err)),
+        }
     }
 
     fn handle_envents(&mut self) -> color_eyre::Result<()> {
         match event::read()? {
             // it's important to check KeyEventKind::Press to avoid handling key release events
             Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
-            Event::Mouse(_) => {}
+            Event::Mouse(mouse) => self.on_mouse_event(mouse),
             Event::Resize(_, _) => {}
             _ => {}
         }
@@ -145,48 +558,141 @@ impl App {
     }
 
     fn on_key_event(&mut self, key: KeyEvent) {
+        self.status = None;
         match self.current_screen {
             CurrentScreen::Main => match (key.modifiers, key.code) {
+                (_, KeyCode::Esc) if !self.search.is_empty() => self.clear_search(),
+                (_, KeyCode::Char('/')) => self.start_search(),
+                (_, KeyCode::Char(':')) => {
+                    self.current_screen = CurrentScreen::Query;
+                    self.buffer = self
+                        .filter
+                        .query
+                        .as_ref()
+                        .map(Query::to_string)
+                        .unwrap_or_default();
+                    self.character_index = self.buffer.chars().count();
+                }
+                (_, KeyCode::Char('n')) if !self.search.is_empty() => self.jump_to_match(true),
+                (_, KeyCode::Char('N')) if !self.search.is_empty() => self.jump_to_match(false),
                 (_, KeyCode::Esc | KeyCode::Char('q'))
                 | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
                     self.current_screen = CurrentScreen::Exiting
                 }
+                (KeyModifiers::SHIFT, KeyCode::Up) | (_, KeyCode::Char('K')) => {
+                    if let Some(id) = self.selected_id() {
+                        let result = self.move_task(id, true);
+                        self.report(result);
+                    }
+                }
+                (KeyModifiers::SHIFT, KeyCode::Down) | (_, KeyCode::Char('J')) => {
+                    if let Some(id) = self.selected_id() {
+                        let result = self.move_task(id, false);
+                        self.report(result);
+                    }
+                }
                 (_, KeyCode::Up) => self.previous(),
                 (_, KeyCode::Down) => self.next(),
                 (_, KeyCode::Enter) => {
-                    // TODO: Check if there are notes to edit. BUG!!
-                    self.current_screen = CurrentScreen::Editing;
-                    self.editing = true;
-                    match self.current_editing {
-                        CurrentEditing::Description => self
-                            .buffer
-                            .push_str(&self.tasks[self.state.selected().unwrap()].description()),
-                        CurrentEditing::Body => self
-                            .buffer
-                            .push_str(&self.tasks[self.state.selected().unwrap()].body()),
+                    if let Some(index) = self.selected() {
+                        self.current_screen = CurrentScreen::Editing;
+                        self.editing = true;
+                        self.load_field(index);
                     }
-                    self.character_index = self.buffer.chars().count();
                 }
                 (_, KeyCode::Tab) => {
-                    self.change_task_done(self.state.selected().unwrap())
-                        .unwrap();
-                    self.save_to_file().unwrap();
+                    if let Some(id) = self.selected_id() {
+                        let result = self.cycle_task_status(id);
+                        self.report(result);
+                    }
                 }
                 (_, KeyCode::Char('n') | KeyCode::Char('N')) => {
-                    self.current_screen = CurrentScreen::Editing;
-                    self.editing = true;
-                    self.buffer.push_str("Type something...");
-                    self.character_index = self.buffer.chars().count();
-                    self.add_task(Task::from_description(&self.buffer).unwrap())
-                        .unwrap_or_default();
-                    self.state.select_last();
+                    // new tasks join the active filter and project view so
+                    // they stay listed
+                    let project = match &self.active_view {
+                        View::Project(name) => Some(name.clone()),
+                        _ => self.filter.project.clone(),
+                    };
+                    let task = Task::from_description("Type something...").map(|mut task| {
+                        task.set_tags(self.filter.tags.clone());
+                        task.set_project(project);
+                        task
+                    });
+                    match task.and_then(|task| self.add_task(task)) {
+                        Ok(()) if self.current_screen == CurrentScreen::Main => {
+                            self.current_screen = CurrentScreen::Editing;
+                            self.current_editing = CurrentEditing::Description;
+                            self.editing = true;
+                            if let Some(index) = self.selected() {
+                                self.load_field(index);
+                            }
+                        }
+                        result => self.report(result),
+                    }
                 }
-                (_, KeyCode::Delete) => {
+                (_, KeyCode::Delete) if self.selected().is_some() => {
                     self.current_screen = CurrentScreen::Deleting;
                 }
-                (_, KeyCode::Char('w') | KeyCode::Char('W')) => {
-                    self.hide_done().unwrap_or_default()
+                (_, KeyCode::Char('+') | KeyCode::Char('=')) => {
+                    if let Some(index) = self.selected() {
+                        let task = &self.tasks[index];
+                        let priority = task.priority().raise();
+                        let result = self.change_task_priority(task.get_id(), priority);
+                        self.report(result);
+                    }
+                }
+                (_, KeyCode::Char('-')) => {
+                    if let Some(index) = self.selected() {
+                        let task = &self.tasks[index];
+                        let priority = task.priority().lower();
+                        let result = self.change_task_priority(task.get_id(), priority);
+                        self.report(result);
+                    }
+                }
+                (_, KeyCode::Char('s') | KeyCode::Char('S')) => self.cycle_sort(),
+                (_, KeyCode::Char('r') | KeyCode::Char('R')) => self.reverse_sort(),
+                (_, KeyCode::Char('>')) => {
+                    let result = self.indent();
+                    self.report(result);
+                }
+                (_, KeyCode::Char('<')) => {
+                    let result = self.outdent();
+                    self.report(result);
+                }
+                (_, KeyCode::Right) => self.set_collapsed(false),
+                (_, KeyCode::Left) => self.set_collapsed(true),
+                (_, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                    self.cascade = !self.cascade;
+                    self.status = Some(if self.cascade {
+                        String::from("Completing a task also completes its subtasks")
+                    } else {
+                        String::from("Completing a task leaves its subtasks alone")
+                    });
+                }
+                (_, KeyCode::Char('f') | KeyCode::Char('F')) => {
+                    self.current_screen = CurrentScreen::Picker;
+                    let entries = self.picker_entries().len();
+                    self.picker.select((entries > 0).then_some(0));
+                }
+                (_, KeyCode::Char('t') | KeyCode::Char('T')) => {
+                    if let Some(id) = self.selected_id() {
+                        let result = self.toggle_timer(id);
+                        self.report(result);
+                    }
+                }
+                (_, KeyCode::Char('[')) => self.step_view(false),
+                (_, KeyCode::Char(']')) => self.step_view(true),
+                (_, KeyCode::Char(digit @ '1'..='9')) => {
+                    let row = digit as usize - '1' as usize;
+                    if let Some(view) = self.views().into_iter().nth(row) {
+                        self.set_view(view);
+                    }
+                }
+                (_, KeyCode::Char('v') | KeyCode::Char('V')) => {
+                    let result = self.save_view();
+                    self.report(result);
                 }
+                (_, KeyCode::Char('w') | KeyCode::Char('W')) => self.hide_done(),
                 (_, KeyCode::Char('h') | KeyCode::Char('H')) => {
                     self.current_screen = CurrentScreen::Help
                 }
@@ -195,17 +701,20 @@ impl App {
             },
             CurrentScreen::Editing => match (key.modifiers, key.code) {
                 (_, KeyCode::Enter) => {
-                    self.current_screen = CurrentScreen::Main;
-                    self.editing = false;
-                    match self.current_editing {
-                        CurrentEditing::Description => self.tasks[self.state.selected().unwrap()]
-                            .set_description(self.buffer.clone()),
-                        CurrentEditing::Body => {
-                            self.tasks[self.state.selected().unwrap()].set_body(self.buffer.clone())
+                    match self.commit_edit() {
+                        Ok(()) => {}
+                        Err(err @ TaskError::Conflict { .. }) => return self.report(Err(err)),
+                        Err(err) => {
+                            // keep editing so the text can be fixed
+                            self.status = Some(err.to_string());
+                            return;
                         }
                     }
+                    if self.current_screen == CurrentScreen::Editing {
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    self.editing = false;
                     self.buffer.clear();
-                    self.save_to_file().unwrap();
                 }
                 (_, KeyCode::Left) => self.move_cursor_left(),
                 (_, KeyCode::Right) => self.move_cursor_right(),
@@ -215,43 +724,25 @@ impl App {
                     self.buffer.clear();
                 }
                 (_, KeyCode::Tab) => {
-                    match self.current_editing {
-                        CurrentEditing::Description => {
-                            self.current_editing = CurrentEditing::Body;
-                            self.tasks[self.state.selected().unwrap()]
-                                .set_description(self.buffer.clone());
-                            self.buffer.clear();
-                            self.buffer
-                                .push_str(&self.tasks[self.state.selected().unwrap()].body());
-                            self.character_index = self.buffer.chars().count();
-                        }
-                        CurrentEditing::Body => {
-                            self.current_editing = CurrentEditing::Description;
-                            self.tasks[self.state.selected().unwrap()]
-                                .set_body(self.buffer.clone());
-                            self.buffer.clear();
-                            self.buffer.push_str(
-                                &self.tasks[self.state.selected().unwrap()].description(),
-                            );
-                            self.character_index = self.buffer.chars().count();
+                    match self.commit_edit() {
+                        Ok(()) => {}
+                        Err(err @ TaskError::Conflict { .. }) => return self.report(Err(err)),
+                        Err(err) => {
+                            self.status = Some(err.to_string());
+                            return;
                         }
                     }
-                    self.save_to_file().unwrap();
-                }
-                (_, KeyCode::Backspace) => {
-                    if !self.buffer.is_empty() {
-                        self.delete_char();
+                    if let Some(index) = self.selected() {
+                        self.current_editing = self.current_editing.next();
+                        self.load_field(index);
                     }
                 }
+                (_, KeyCode::Backspace) if !self.buffer.is_empty() => self.delete_char(),
                 (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
                     self.current_screen = CurrentScreen::Exiting;
                     self.editing = false;
                 }
-                (_, KeyCode::Char(value)) => {
-                    if self.editing {
-                        self.enter_char(value);
-                    }
-                }
+                (_, KeyCode::Char(value)) if self.editing => self.enter_char(value),
                 _ => {}
             },
             CurrentScreen::Help => match (key.modifiers, key.code) {
@@ -268,11 +759,97 @@ impl App {
                 }
                 _ => {}
             },
+            CurrentScreen::Conflict => match (key.modifiers, key.code) {
+                (_, KeyCode::Char('r') | KeyCode::Char('R')) => {
+                    self.current_screen = CurrentScreen::Main;
+                    let result = self.reload();
+                    self.report(result);
+                }
+                (_, KeyCode::Char('k') | KeyCode::Char('K')) => {
+                    self.current_screen = CurrentScreen::Main;
+                    let result = self.overwrite();
+                    self.report(result);
+                }
+                (_, KeyCode::Esc) => {
+                    // the change stays in memory; the next save asks again
+                    self.current_screen = CurrentScreen::Main;
+                    self.status = Some(String::from("Not saved: tasks changed on disk"));
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                    self.current_screen = CurrentScreen::Exiting
+                }
+                _ => {}
+            },
+            CurrentScreen::Picker => match (key.modifiers, key.code) {
+                (_, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('f')) => {
+                    self.current_screen = CurrentScreen::Main
+                }
+                (_, KeyCode::Up) => self.picker.select_previous(),
+                (_, KeyCode::Down) => self.picker.select_next(),
+                (_, KeyCode::Enter | KeyCode::Char(' ')) => {
+                    let entries = self.picker_entries();
+                    if let Some(entry) = self.picker.selected().and_then(|i| entries.get(i)) {
+                        let mut filter = self.filter.clone();
+                        filter.toggle(entry);
+                        self.set_filter(filter);
+                    }
+                }
+                (_, KeyCode::Char('c') | KeyCode::Char('C')) => self.set_filter(Filter::default()),
+                _ => {}
+            },
+            CurrentScreen::Search => match (key.modifiers, key.code) {
+                (_, KeyCode::Enter) => self.current_screen = CurrentScreen::Main,
+                (_, KeyCode::Esc) => {
+                    self.current_screen = CurrentScreen::Main;
+                    self.clear_search();
+                }
+                (_, KeyCode::Down) => self.jump_to_match(true),
+                (_, KeyCode::Up) => self.jump_to_match(false),
+                (_, KeyCode::Backspace) => {
+                    let mut query = self.search.clone();
+                    query.pop();
+                    self.set_search(query);
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                    self.current_screen = CurrentScreen::Exiting
+                }
+                (_, KeyCode::Char(value)) => {
+                    let mut query = self.search.clone();
+                    query.push(value);
+                    self.set_search(query);
+                }
+                _ => {}
+            },
+            CurrentScreen::Query => match (key.modifiers, key.code) {
+                (_, KeyCode::Enter) => {
+                    if let Err(err) = self.set_query(&self.buffer.clone()) {
+                        // keep the prompt open so the query can be fixed
+                        self.status = Some(err.to_string());
+                        return;
+                    }
+                    self.current_screen = CurrentScreen::Main;
+                    self.buffer.clear();
+                }
+                (_, KeyCode::Esc) => {
+                    self.current_screen = CurrentScreen::Main;
+                    self.buffer.clear();
+                }
+                (_, KeyCode::Left) => self.move_cursor_left(),
+                (_, KeyCode::Right) => self.move_cursor_right(),
+                (_, KeyCode::Backspace) => self.delete_char(),
+                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                    self.current_screen = CurrentScreen::Exiting
+                }
+                (_, KeyCode::Char(value)) => self.enter_char(value),
+                _ => {}
+            },
             CurrentScreen::Deleting => match (key.modifiers, key.code) {
                 (_, KeyCode::Char('y') | KeyCode::Char('Y')) => {
-                    self.remove_task(self.state.selected().unwrap())
-                        .unwrap_or_default();
                     self.current_screen = CurrentScreen::Main;
+                    if let Some(id) = self.selected_id() {
+                        let result = self.remove_task(id);
+                        self.report(result);
+                    }
                 }
                 (_, KeyCode::Char('n') | KeyCode::Char('N')) => {
                     self.current_screen = CurrentScreen::Main
@@ -282,30 +859,277 @@ impl App {
         }
     }
 
+    /// Dragging a task with the left button moves it through the list, one
+    /// sibling at a time, and saves the new order once the button is let go.
+    fn on_mouse_event(&mut self, mouse: MouseEvent) {
+        if self.current_screen != CurrentScreen::Main {
+            return;
+        }
+        let row = self.row_at(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(row) = row {
+                    self.state.select(Some(row));
+                    self.drag = self.selected_id().map(|id| (id, false));
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let (Some((id, _)), Some(target), Some(start)) =
+                    (self.drag, row, self.state.selected())
+                else {
+                    return;
+                };
+                // a sibling's subtasks are stepped over whole, so stop once
+                // the target is reached or passed
+                let up = target < start;
+                while let Some(current) = self.state.selected() {
+                    if (up && current <= target) || (!up && current >= target) {
+                        break;
+                    }
+                    match self.shift_task(id, up) {
+                        Ok(true) => self.drag = Some((id, true)),
+                        Ok(false) => break,
+                        Err(err) => {
+                            self.status = Some(err.to_string());
+                            break;
+                        }
+                    }
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some((_, true)) = self.drag.take() {
+                    let result = self.save_order();
+                    self.report(result);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Row of the view drawn at the given terminal cell, if any.
+    fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inner = self.list_area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+        let row = usize::from(row - inner.y) + self.state.offset();
+        (row < self.view().len()).then_some(row)
+    }
+
     fn loading(&mut self) {
         self.loading = !self.loading;
     }
 
-    pub fn add_task(&mut self, mut task: Task) -> color_eyre::Result<()> {
-        if self.with_json {
-            task.set_id(self.index());
-            self.write_to_json(self.path.clone())?;
-        } else {
-            let mut file = File::options()
-                .write(true)
-                .append(true)
-                .open(&self.path_bin)?;
-            task.set_id(self.index());
-            writeln!(file, "{}", task.to_line())?;
-            file.flush()?; // ensures writing
+    /// Index of the task with the given id.
+    pub fn position(&self, id: usize) -> Result<usize, TaskError> {
+        self.tasks
+            .iter()
+            .position(|task| task.get_id() == id)
+            .ok_or(TaskError::NotFound { id })
+    }
+
+    /// Indices into `tasks` in the order they are listed, leaving out
+    /// completed tasks while they are hidden (unless the query asks for
+    /// them), tasks outside the filter or the active view and
+    /// subtasks of collapsed tasks. The list selection is a row of this view,
+    /// not an index into `tasks`.
+    pub fn view(&self) -> Vec<usize> {
+        self.tree().into_iter().map(|(index, _)| index).collect()
+    }
+
+    /// Same rows as `view`, each with its depth in the task tree. Subtasks
+    /// follow their parent, sorted among their siblings; a subtask whose
+    /// parent is not listed is shown at the top level.
+    pub fn tree(&self) -> Vec<(usize, usize)> {
+        let now = Local::now().naive_local();
+        let show_done =
+            self.show_done || self.filter.shows_closed() || self.active_view.shows_closed();
+        let mut listed: Vec<usize> = (0..self.tasks.len())
+            .filter(|&i| show_done || !self.tasks[i].completed())
+            .filter(|&i| self.filter.matches(&self.tasks[i]))
+            .filter(|&i| self.active_view.matches(&self.tasks[i], now))
+            .collect();
+        // stable, so equal tasks keep their stored order
+        listed.sort_by(|&a, &b| {
+            self.sort
+                .compare(&self.tasks[a], &self.tasks[b], self.sort_reversed)
+        });
+        if self.sort == SortMode::Manual && self.sort_reversed {
+            listed.reverse();
+        }
+
+        let listed_ids: HashSet<usize> = listed.iter().map(|&i| self.tasks[i].get_id()).collect();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for &i in &listed {
+            match self.tasks[i].parent() {
+                Some(parent) if listed_ids.contains(&parent) => {
+                    children.entry(parent).or_default().push(i)
+                }
+                _ => roots.push(i),
+            }
+        }
+
+        let mut rows = Vec::with_capacity(listed.len());
+        let mut seen = HashSet::new();
+        for root in roots {
+            self.walk(root, 0, true, &children, &mut seen, &mut rows);
+        }
+        // only tasks in a parent cycle are left; list them flat
+        for i in listed {
+            if seen.insert(i) {
+                rows.push((i, 0));
+            }
+        }
+        rows
+    }
+
+    fn walk(
+        &self,
+        index: usize,
+        depth: usize,
+        shown: bool,
+        children: &HashMap<usize, Vec<usize>>,
+        seen: &mut HashSet<usize>,
+        rows: &mut Vec<(usize, usize)>,
+    ) {
+        if !seen.insert(index) {
+            return;
+        }
+        if shown {
+            rows.push((index, depth));
+        }
+        let id = self.tasks[index].get_id();
+        let shown = shown && !self.collapsed.contains(&id);
+        for &child in children.get(&id).into_iter().flatten() {
+            self.walk(child, depth + 1, shown, children, seen, rows);
+        }
+    }
+
+    /// Lookups over `tasks` for when many tasks are asked about at once.
+    pub fn links(&self) -> Links<'_> {
+        Links::new(&self.tasks)
+    }
+
+    /// Indices of the direct subtasks of the task with `id`.
+    pub fn children(&self, id: usize) -> Vec<usize> {
+        self.links().children(id).to_vec()
+    }
+
+    /// Indices of every task below the task with `id`.
+    pub fn descendants(&self, id: usize) -> Vec<usize> {
+        self.links().descendants(id)
+    }
+
+    /// Completed and total direct subtasks of the task at `index`, or `None`
+    /// when it has none.
+    pub fn progress(&self, index: usize) -> Option<(usize, usize)> {
+        self.links().progress(index)
+    }
+
+    /// Index of the selected task, if the selection points at one.
+    pub fn selected(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|row| self.view().get(row).copied())
+    }
+
+    /// Selects the task with `id` wherever it is listed now. When it is not
+    /// listed, the selection stays on the same row, clamped to the view.
+    fn select_id(&mut self, id: Option<usize>) {
+        let view = self.view();
+        let row = id.and_then(|id| view.iter().position(|&i| self.tasks[i].get_id() == id));
+        match (row, self.state.selected()) {
+            _ if view.is_empty() => self.state.select(None),
+            (Some(row), _) => self.state.select(Some(row)),
+            (None, Some(row)) if row >= view.len() => self.state.select(Some(view.len() - 1)),
+            (None, None) => self.state.select(Some(0)),
+            _ => {}
+        }
+    }
+
+    /// Id of the selected task.
+    pub fn selected_id(&self) -> Option<usize> {
+        self.selected().map(|index| self.tasks[index].get_id())
+    }
+
+    /// Shows a failed operation in the status line instead of bailing out.
+    /// Shows why an action failed in the status line. A write rejected
+    /// because another process changed the data file opens the `Conflict`
+    /// prompt instead; the change stays in `tasks`, so keeping it is just a
+    /// matter of overwriting.
+    fn report(&mut self, result: Result<(), TaskError>) {
+        match result {
+            Ok(()) => {}
+            Err(TaskError::Conflict { .. }) => {
+                self.current_screen = CurrentScreen::Conflict;
+                self.editing = false;
+                self.buffer.clear();
+            }
+            Err(err) => self.status = Some(err.to_string()),
+        }
+    }
+
+    /// Fills the buffer with the field being edited of the task at `index`.
+    fn load_field(&mut self, index: usize) {
+        let task = &self.tasks[index];
+        self.buffer = match self.current_editing {
+            CurrentEditing::Description => with_tokens(task),
+            CurrentEditing::Body => task.body(),
+            CurrentEditing::Due => task.due().map(|due| due.to_string()).unwrap_or_default(),
+            CurrentEditing::BlockedBy => blocked_by(task),
+            CurrentEditing::Recurrence => task
+                .recurrence()
+                .map(|recurrence| recurrence.to_string())
+                .unwrap_or_default(),
+        };
+        self.character_index = self.buffer.chars().count();
+    }
+
+    /// Stores the buffer into the field being edited of the selected task.
+    fn commit_edit(&mut self) -> Result<(), TaskError> {
+        let Some(id) = self.selected_id() else {
+            return Ok(());
+        };
+        match self.current_editing {
+            CurrentEditing::Description => self.change_task_text(id, &self.buffer.clone()),
+            CurrentEditing::Body => self.change_task_body(id, self.buffer.clone()),
+            CurrentEditing::Due => {
+                let due = parse_due(&self.buffer, Local::now().naive_local())?;
+                self.change_task_due(id, due)
+            }
+            CurrentEditing::BlockedBy => {
+                let ids = self
+                    .buffer
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|id| !id.is_empty())
+                    .map(|id| {
+                        id.parse::<usize>().map_err(|_| TaskError::Validation {
+                            field: "blocked_by",
+                            reason: format!("`{}` is not a task id", id),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                self.change_task_blockers(id, ids)
+            }
+            CurrentEditing::Recurrence => {
+                let recurrence = parse_recurrence(&self.buffer)?;
+                self.change_task_recurrence(id, recurrence)
+            }
         }
-        self.tasks.push(task);
-        self.state.select(Some(0)); // reset state
-        Ok(())
     }
 
-    pub fn index(&self) -> usize {
-        self.tasks.len()
+    /// Adds `task` under a fresh id from the store, which it keeps for good.
+    pub fn add_task(&mut self, mut task: Task) -> Result<(), TaskError> {
+        let id = self.store.next_id()?;
+        task.set_id(id);
+        let now = Local::now();
+        task.set_stamp(Stamp::Created, Some(now));
+        task.set_stamp(Stamp::Updated, Some(now));
+        let result = self.store.append(&task);
+        self.tasks.push(task);
+        self.select_id(Some(id));
+        result
     }
 
     fn move_cursor_left(&mut self) {
@@ -348,17 +1172,6 @@ impl App {
         }
     }
 
-    pub fn read_from_json(path: PathBuf) -> std::io::Result<Vec<Task>> {
-        let content = fs::read_to_string(path)?;
-        let tasks: Vec<Task> = serde_json::from_str(&content)?;
-        Ok(tasks)
-    }
-
-    pub fn write_to_json(&self, path: PathBuf) -> std::io::Result<()> {
-        let content = serde_json::to_string_pretty(&self.tasks)?;
-        fs::write(path, content)
-    }
-
     // TODO: Error handling
     pub fn tasks_into_string(&self) -> String {
         let mut task_str = String::new();
@@ -366,7 +1179,7 @@ impl App {
             task_str.push_str(&format!(
                 "{} {} {} \n",
                 f.description(),
-                is_completed(f.completed()),
+                f.status().marker(),
                 f.body()
             ))
         });
@@ -374,94 +1187,637 @@ impl App {
     }
 
     // TODO: Error Handling
-    pub fn remove_task(&mut self, index: usize) -> color_eyre::Result<()> {
-        self.tasks.remove(index);
-        self.save_to_file()?;
-        Ok(())
+    /// Removes the task with `id`. Its subtasks move up to its parent.
+    pub fn remove_task(&mut self, id: usize) -> Result<(), TaskError> {
+        let task = self.tasks.remove(self.position(id)?);
+        let mut changed = self.children(id);
+        for &child in &changed {
+            self.tasks[child].set_parent(task.parent());
+        }
+        for i in 0..self.tasks.len() {
+            let mut blocked_by = self.tasks[i].blocked_by().clone();
+            if blocked_by.remove(&id) {
+                self.tasks[i].set_blocked_by(blocked_by);
+                changed.push(i);
+            }
+        }
+        self.select_id(None);
+        self.save_all(&changed)?;
+        self.store.delete(id)
     }
 
-    pub fn clean_tasks(&mut self) -> color_eyre::Result<()> {
+    pub fn clean_tasks(&mut self) -> Result<(), TaskError> {
         self.tasks.clear();
-        if self.with_json {
-            self.write_to_json(self.path.clone())?;
+        self.state.select(None);
+        self.store.clear()
+    }
+
+    pub fn change_task_description(&mut self, id: usize, text: String) -> Result<(), TaskError> {
+        if text.is_empty() {
+            return Err(TaskError::Validation {
+                field: "description",
+                reason: String::from("text is empty"),
+            });
+        }
+        let index = self.position(id)?;
+        self.tasks[index].set_description(text);
+        self.save_at(index)
+    }
+
+    /// Sets the description from text typed in the editor. The `+project`,
+    /// `@context` and `#tag` tokens in it replace the project and tags.
+    pub fn change_task_text(&mut self, id: usize, input: &str) -> Result<(), TaskError> {
+        let tokens = parse_tokens(input);
+        if tokens.description.is_empty() {
+            return Err(TaskError::Validation {
+                field: "description",
+                reason: String::from("text is empty"),
+            });
+        }
+        let index = self.position(id)?;
+        let selected = self.selected_id();
+        let task = &mut self.tasks[index];
+        task.set_description(tokens.description);
+        task.set_project(tokens.project);
+        task.set_tags(tokens.tags);
+        self.select_id(selected);
+        self.save_at(index)
+    }
+
+    pub fn change_task_tags(
+        &mut self,
+        id: usize,
+        tags: BTreeSet<String>,
+        project: Option<String>,
+    ) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        let selected = self.selected_id();
+        self.tasks[index].set_tags(tags);
+        self.tasks[index].set_project(project);
+        self.select_id(selected);
+        self.save_at(index)
+    }
+
+    pub fn change_task_body(&mut self, id: usize, body: String) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        self.tasks[index].set_body(body);
+        self.save_at(index)
+    }
+
+    pub fn change_task_due(&mut self, id: usize, due: Option<Due>) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        self.tasks[index].set_due(due);
+        self.save_at(index)
+    }
+
+    pub fn change_task_priority(&mut self, id: usize, priority: Priority) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        let selected = self.selected_id();
+        self.tasks[index].set_priority(priority);
+        self.select_id(selected);
+        self.save_at(index)
+    }
+
+    /// Marks the task with `id` as done, or reopens it as todo. Closed and
+    /// open tasks respectively are left as they are.
+    pub fn set_task_done(&mut self, id: usize, done: bool) -> Result<(), TaskError> {
+        if self.tasks[self.position(id)?].completed() == done {
+            return Ok(());
+        }
+        let status = if done { Status::Done } else { Status::Todo };
+        self.change_task_status(id, status)
+    }
+
+    /// Moves the task with `id` to the status after its current one in the
+    /// configured cycle.
+    pub fn cycle_task_status(&mut self, id: usize) -> Result<(), TaskError> {
+        let status = self.tasks[self.position(id)?].status();
+        self.change_task_status(id, self.config.next_status(status))
+    }
+
+    /// Moves the task with `id` to `status`. When that closes or reopens it
+    /// and `cascade` is on, its subtasks follow. A task can't be done while
+    /// it is blocked. Closing a recurring task adds its next occurrence.
+    pub fn change_task_status(&mut self, id: usize, status: Status) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        let links = self.links();
+        let blockers = links.blockers(index);
+        if status == Status::Done && !blockers.is_empty() {
+            return Err(TaskError::Blocked {
+                id,
+                by: blockers.iter().map(|&i| self.tasks[i].get_id()).collect(),
+            });
+        }
+        let was_closed = self.tasks[index].completed();
+        let closed = status.is_closed();
+        let cascading = if self.cascade && closed != was_closed {
+            links.descendants(id)
         } else {
-            if let Some(parent) = self.path_bin.parent() {
-                fs::create_dir_all(parent)?;
+            Vec::new()
+        };
+        // a subtask can't be done while waiting on a task outside the
+        // cascade, so the whole cascade is refused
+        if status == Status::Done {
+            let closing: HashSet<usize> = cascading.iter().copied().collect();
+            for &i in &cascading {
+                let blockers: Vec<usize> = links
+                    .blockers(i)
+                    .into_iter()
+                    .filter(|&b| b != index && !closing.contains(&b))
+                    .map(|b| self.tasks[b].get_id())
+                    .collect();
+                if !blockers.is_empty() {
+                    return Err(TaskError::Blocked {
+                        id: self.tasks[i].get_id(),
+                        by: blockers,
+                    });
+                }
             }
-            File::create(&self.path_bin)?;
         }
 
-        Ok(())
+        let selected = self.selected_id();
+        self.tasks[index].set_status(status);
+        let mut changed = vec![index];
+        for i in cascading {
+            if self.tasks[i].completed() != closed {
+                self.tasks[i].set_status(status);
+                changed.push(i);
+            }
+        }
+
+        if closed {
+            let now = Local::now();
+            for &i in &changed {
+                self.tasks[i].stop_timer(now);
+            }
+        }
+
+        // the rule moves on to the next occurrence, so reopening and closing
+        // this one again doesn't spawn another copy
+        let mut spawned = Vec::new();
+        if closed && !was_closed {
+            let now = Local::now().naive_local();
+            for &i in &changed {
+                if let Some(next) = next_occurrence(&self.tasks[i], now) {
+                    self.tasks[i].set_recurrence(None);
+                    spawned.push(next);
+                }
+            }
+        }
+
+        // the next occurrences are added even when saving conflicts, so
+        // keeping this version of the list doesn't lose them
+        let mut result = self.save_all(&changed);
+        for task in spawned {
+            result = result.and(self.add_task(task));
+        }
+        self.select_id(selected);
+        result
+    }
+
+    /// Starts the timer on the task with `id`, stopping the one running on
+    /// any other task, or stops it when it is already running.
+    pub fn toggle_timer(&mut self, id: usize) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        let now = Local::now();
+        self.clock = now;
+        if self.tasks[index].is_tracking() {
+            self.tasks[index].stop_timer(now);
+            return self.save_at(index);
+        }
+        let mut changed = Vec::new();
+        for i in 0..self.tasks.len() {
+            if self.tasks[i].is_tracking() {
+                self.tasks[i].stop_timer(now);
+                changed.push(i);
+            }
+        }
+        self.tasks[index].start_timer(now);
+        changed.push(index);
+        self.save_all(&changed)
     }
 
-    pub fn change_task_description(
+    pub fn change_task_recurrence(
         &mut self,
-        index: usize,
-        text: String,
-    ) -> color_eyre::Result<()> {
-        self.tasks[index].set_description(text);
-        self.save_to_file()?;
-        Ok(())
+        id: usize,
+        recurrence: Option<Recurrence>,
+    ) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        self.tasks[index].set_recurrence(recurrence);
+        self.save_at(index)
+    }
+
+    /// Indices of the unfinished tasks the task at `index` waits on.
+    pub fn blockers(&self, index: usize) -> Vec<usize> {
+        self.links().blockers(index)
+    }
+
+    /// Replaces the tasks the task with `id` waits on. Every id has to
+    /// exist and none may end up waiting on this task in turn.
+    pub fn change_task_blockers(
+        &mut self,
+        id: usize,
+        blocked_by: BTreeSet<usize>,
+    ) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        for &blocker in blocked_by.difference(self.tasks[index].blocked_by()) {
+            self.position(blocker)?;
+            if let Some(ids) = find_cycle(&self.tasks, id, blocker) {
+                return Err(TaskError::Cycle { ids });
+            }
+        }
+        self.tasks[index].set_blocked_by(blocked_by);
+        self.save_at(index)
+    }
+
+    /// Makes the task with `id` a subtask of the task with id `parent`, or a
+    /// top level task when `parent` is `None`.
+    pub fn change_task_parent(
+        &mut self,
+        id: usize,
+        parent: Option<usize>,
+    ) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        if let Some(parent) = parent {
+            let parent_index = self.position(parent)?;
+            if parent == id || self.descendants(id).contains(&parent_index) {
+                return Err(TaskError::Validation {
+                    field: "parent",
+                    reason: format!("task {} cannot be a subtask of itself", id),
+                });
+            }
+        }
+        let selected = self.selected_id();
+        self.tasks[index].set_parent(parent);
+        self.select_id(selected);
+        self.save_at(index)
+    }
+
+    /// Moves the selected task under the sibling listed just above it.
+    pub fn indent(&mut self) -> Result<(), TaskError> {
+        let (Some(row), Some(index)) = (self.state.selected(), self.selected()) else {
+            return Ok(());
+        };
+        let tree = self.tree();
+        let depth = tree[row].1;
+        let sibling = tree[..row]
+            .iter()
+            .rev()
+            .take_while(|(_, d)| *d >= depth)
+            .find(|(_, d)| *d == depth);
+        let Some(&(sibling, _)) = sibling else {
+            return Ok(());
+        };
+        let parent = self.tasks[sibling].get_id();
+        self.collapsed.remove(&parent);
+        self.change_task_parent(self.tasks[index].get_id(), Some(parent))
+    }
+
+    /// Moves the selected task up a level, next to its parent.
+    pub fn outdent(&mut self) -> Result<(), TaskError> {
+        let Some(index) = self.selected() else {
+            return Ok(());
+        };
+        let Some(parent) = self.tasks[index].parent() else {
+            return Ok(());
+        };
+        let grandparent = self
+            .position(parent)
+            .ok()
+            .and_then(|i| self.tasks[i].parent());
+        self.change_task_parent(self.tasks[index].get_id(), grandparent)
     }
 
-    pub fn change_task_done(&mut self, index: usize) -> color_eyre::Result<()> {
-        self.tasks[index].set_completed();
-        self.save_to_file()?;
+    /// Moves the task with `id` above the sibling listed before it (or below
+    /// the one after it) and saves the new order. Only the manual order can be
+    /// rearranged.
+    pub fn move_task(&mut self, id: usize, up: bool) -> Result<(), TaskError> {
+        if self.shift_task(id, up)? {
+            self.save_order()?;
+        }
         Ok(())
     }
 
-    pub fn hide_done(&mut self) -> color_eyre::Result<()> {
+    /// Persists the order of `tasks` without rewriting the tasks.
+    fn save_order(&mut self) -> Result<(), TaskError> {
+        let ids: Vec<usize> = self.tasks.iter().map(Task::get_id).collect();
+        self.store.reorder(&ids)
+    }
+
+    /// Reorders `tasks` for `move_task` without saving. Returns whether the
+    /// task moved.
+    fn shift_task(&mut self, id: usize, up: bool) -> Result<bool, TaskError> {
+        if self.sort != SortMode::Manual {
+            return Err(TaskError::Validation {
+                field: "sort",
+                reason: String::from("tasks can only be moved in the manual order"),
+            });
+        }
+        let index = self.position(id)?;
+        let tree = self.tree();
+        let Some(row) = tree.iter().position(|&(i, _)| i == index) else {
+            return Ok(false);
+        };
+        let depth = tree[row].1;
+        // the neighbour at the same depth, without leaving the parent
+        let sibling = if up {
+            tree[..row]
+                .iter()
+                .rev()
+                .take_while(|(_, d)| *d >= depth)
+                .find(|(_, d)| *d == depth)
+        } else {
+            tree[row + 1..]
+                .iter()
+                .take_while(|(_, d)| *d >= depth)
+                .find(|(_, d)| *d == depth)
+        };
+        let Some(&(sibling, _)) = sibling else {
+            return Ok(false);
+        };
+        let task = self.tasks.remove(index);
+        self.tasks.insert(sibling, task);
+        self.select_id(Some(id));
+        Ok(true)
+    }
+
+    /// Shows or hides the subtasks of the selected task.
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        if collapsed {
+            self.collapsed.insert(id);
+        } else {
+            self.collapsed.remove(&id);
+        }
+        self.select_id(Some(id));
+    }
+
+    pub fn hide_done(&mut self) {
+        let id = self.selected_id();
         self.show_done = !self.show_done;
+        self.select_id(id);
+    }
+
+    pub fn set_filter(&mut self, filter: Filter) {
+        let id = self.selected_id();
+        self.filter = filter;
+        self.select_id(id);
+    }
+
+    /// Narrows the view to tasks matching `input`, or drops the query when
+    /// `input` is blank. A query that does not parse leaves the filter alone.
+    pub fn set_query(&mut self, input: &str) -> Result<(), TaskError> {
+        let query = Query::parse(input, Local::now().naive_local())?;
+        let mut filter = self.filter.clone();
+        filter.query = (!query.is_empty()).then_some(query);
+        self.set_filter(filter);
         Ok(())
     }
 
-    pub fn remove_trailing_newline(&self) -> color_eyre::Result<()> {
-        let file = File::options()
-            .read(true)
-            .write(true)
-            .open(&self.path_bin)?;
-        let buf = BufReader::new(file);
+    /// Rows of the views sidebar: everything, the inbox, today, overdue,
+    /// one per project in use and the saved views from the config. Saved
+    /// queries that no longer parse are left out.
+    pub fn views(&self) -> Vec<View> {
+        let now = Local::now().naive_local();
+        let projects: BTreeSet<&str> = self.tasks.iter().filter_map(Task::project).collect();
+        let saved = self.config.views.iter().filter_map(|view| {
+            let query = Query::parse(&view.query, now).ok()?;
+            Some(View::Saved(view.name.clone(), query))
+        });
+        [View::All, View::Inbox, View::Today, View::Overdue]
+            .into_iter()
+            .chain(
+                projects
+                    .into_iter()
+                    .map(|name| View::Project(name.to_owned())),
+            )
+            .chain(saved)
+            .collect()
+    }
+
+    /// Number of tasks `view` lists, leaving the filter aside.
+    pub fn view_count(&self, view: &View) -> usize {
+        let now = Local::now().naive_local();
+        let show_done = self.show_done || view.shows_closed();
+        self.tasks
+            .iter()
+            .filter(|task| show_done || !task.completed())
+            .filter(|task| view.matches(task, now))
+            .count()
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        let id = self.selected_id();
+        self.active_view = view;
+        self.select_id(id);
+    }
 
-        let mut lines: Vec<String> = buf.lines().filter_map(|line| line.ok()).collect();
+    /// Switches to the next (or previous) view in the sidebar, wrapping
+    /// around.
+    pub fn step_view(&mut self, forward: bool) {
+        let views = self.views();
+        let current = views.iter().position(|view| *view == self.active_view);
+        let row = match (current, forward) {
+            (Some(row), true) => (row + 1) % views.len(),
+            (Some(row), false) => (row + views.len() - 1) % views.len(),
+            (None, _) => 0,
+        };
+        self.set_view(views[row].clone());
+    }
 
-        if let Some(last) = lines.last() {
-            if last.is_empty() {
-                lines.pop();
+    /// Saves the query typed after `:` as a view named after it and switches
+    /// to that view. Without a query, removes the active saved view instead.
+    pub fn save_view(&mut self) -> Result<(), TaskError> {
+        if let Some(query) = self.filter.query.clone() {
+            let source = query.to_string();
+            if !self.config.views.iter().any(|view| view.query == source) {
+                self.config.views.push(SavedView {
+                    name: source.clone(),
+                    query: source.clone(),
+                });
             }
+            let name = self
+                .config
+                .views
+                .iter()
+                .find(|view| view.query == source)
+                .map(|view| view.name.clone())
+                .unwrap_or(source);
+            self.status = Some(format!("Saved view `{}`", name));
+            self.filter.query = None;
+            self.set_view(View::Saved(name, query));
+        } else if let View::Saved(name, _) = &self.active_view {
+            let name = name.clone();
+            self.config.views.retain(|view| view.name != name);
+            self.status = Some(format!("Removed view `{}`", name));
+            self.set_view(View::All);
+        } else {
+            self.status = Some(String::from("Type a query with `:` to save it as a view"));
+            return Ok(());
         }
-        let mut file = File::create(&self.path_bin)?;
-        for line in lines {
-            writeln!(file, "{}", line)?;
+        match &self.config_path {
+            Some(path) => self.config.save(path),
+            None => Ok(()),
         }
-        Ok(())
     }
 
-    pub fn save_to_file(&self) -> color_eyre::Result<()> {
-        if self.with_json {
-            self.write_to_json(self.path.clone())?;
+    /// Every project and tag in use, projects first, then the periods, for
+    /// the filter picker.
+    ///
+    /// Entries of the current filter are kept even when no task uses them
+    /// anymore, so they can still be turned off.
+    pub fn picker_entries(&self) -> Vec<PickerEntry> {
+        let projects: BTreeSet<&str> = self
+            .tasks
+            .iter()
+            .filter_map(Task::project)
+            .chain(self.filter.project.as_deref())
+            .collect();
+        let tags: BTreeSet<&String> = self
+            .tasks
+            .iter()
+            .flat_map(Task::tags)
+            .chain(&self.filter.tags)
+            .collect();
+        projects
+            .into_iter()
+            .map(|name| PickerEntry::Project(name.to_owned()))
+            .chain(tags.into_iter().map(|name| PickerEntry::Tag(name.clone())))
+            .chain(
+                [Stamp::Completed, Stamp::Created, Stamp::Updated]
+                    .into_iter()
+                    .flat_map(|stamp| {
+                        [PickerEntry::Recent(stamp, 1), PickerEntry::Recent(stamp, 7)]
+                    }),
+            )
+            .collect()
+    }
+
+    /// How the task at `index` matches the search, if one is active.
+    pub fn search_hit(&self, index: usize) -> Option<SearchHit> {
+        search_task(&self.tasks[index], &self.search)
+    }
+
+    /// Rows of the view whose task matches the search.
+    pub fn search_rows(&self) -> Vec<usize> {
+        self.view()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, index)| self.search_hit(index).is_some())
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    pub fn start_search(&mut self) {
+        self.current_screen = CurrentScreen::Search;
+        self.search_origin = self.selected_id();
+        self.search.clear();
+    }
+
+    /// Changes the search text and selects the first match at or below the
+    /// task the search started from, wrapping around. Without a match the
+    /// selection goes back to that task.
+    pub fn set_search(&mut self, query: String) {
+        self.search = query;
+        self.select_id(self.search_origin);
+        let from = self.state.selected().unwrap_or(0);
+        let rows = self.search_rows();
+        let row = rows.iter().find(|&&row| row >= from).or(rows.first());
+        if let Some(&row) = row {
+            self.state.select(Some(row));
+        }
+    }
+
+    /// Selects the next (or previous) matching row, wrapping around.
+    pub fn jump_to_match(&mut self, forward: bool) {
+        if self.search.is_empty() {
+            return;
+        }
+        let rows = self.search_rows();
+        let current = self.state.selected();
+        let row = if forward {
+            rows.iter()
+                .find(|&&row| current.is_none_or(|current| row > current))
+                .or(rows.first())
         } else {
-            let mut file = File::options()
-                .read(true)
-                .write(true)
-                .open(&self.path_bin)?;
-            for line in &self.tasks {
-                writeln!(
-                    file,
-                    "{},{},{}",
-                    line.get_id(),
-                    line.description(),
-                    line.completed()
-                )?;
-            }
-            self.remove_trailing_newline()?;
+            rows.iter()
+                .rev()
+                .find(|&&row| current.is_none_or(|current| row < current))
+                .or(rows.last())
+        };
+        match row {
+            Some(&row) => self.state.select(Some(row)),
+            None => self.status = Some(format!("No task matches `{}`", self.search)),
         }
+    }
+
+    /// Ends the search, staying on the selected task.
+    pub fn clear_search(&mut self) {
+        let id = self.selected_id();
+        self.search.clear();
+        self.search_origin = None;
+        self.select_id(id);
+    }
+
+    pub fn cycle_sort(&mut self) {
+        let id = self.selected_id();
+        self.sort = self.sort.next();
+        self.sort_reversed = false;
+        self.select_id(id);
+    }
+
+    /// Flips the direction of the sort, staying on the selected task.
+    pub fn reverse_sort(&mut self) {
+        let id = self.selected_id();
+        self.sort_reversed = !self.sort_reversed;
+        self.select_id(id);
+    }
+
+    /// Persists a single task after it was changed in place.
+    pub fn save_task(&mut self, id: usize) -> Result<(), TaskError> {
+        let index = self.position(id)?;
+        self.save_at(index)
+    }
+
+    fn save_at(&mut self, index: usize) -> Result<(), TaskError> {
+        self.store.update(&self.tasks[index])
+    }
+
+    /// Persists the tasks at `indices`. Callers make all their in-memory
+    /// changes first, so a `Conflict` leaves `tasks` complete for the prompt
+    /// to keep or throw away.
+    fn save_all(&mut self, indices: &[usize]) -> Result<(), TaskError> {
+        indices.iter().try_for_each(|&i| self.save_at(i))
+    }
+
+    pub fn save_to_file(&mut self) -> Result<(), TaskError> {
+        self.store.save(&self.tasks)
+    }
+
+    /// Replaces the in-memory list with what is on disk, keeping the same
+    /// task selected when it still exists.
+    pub fn reload(&mut self) -> Result<(), TaskError> {
+        let id = self.selected_id();
+        self.tasks = self.store.load()?;
+        self.select_id(id);
         Ok(())
     }
 
+    /// Writes the in-memory list over whatever is on disk.
+    pub fn overwrite(&mut self) -> Result<(), TaskError> {
+        self.store.force_save(&self.tasks)
+    }
+
     pub fn next(&mut self) {
+        let len = self.view().len();
+        if len == 0 {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.tasks.len() - 1 {
+                if i >= len - 1 {
                     0
                 } else {
                     i + 1
@@ -473,10 +1829,14 @@ impl App {
     }
 
     pub fn previous(&mut self) {
+        let len = self.view().len();
+        if len == 0 {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.tasks.len() - 1
+                    len - 1
                 } else {
                     i - 1
                 }
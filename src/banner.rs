@@ -8,4 +8,3 @@ pub const BANNER: &str = r"
 #                                             
 ";
 // style: slant
-// to use with clap lib
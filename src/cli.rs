@@ -0,0 +1,526 @@
+use crate::app::{App, Filter, SortMode};
+use crate::banner::BANNER;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use clap::{Parser, Subcommand, ValueEnum};
+use model::{
+    common::{Stamp, Task, TaskError},
+    due::parse_due,
+    line::{blocked_by, escape, stamp, tags, time_entries},
+    priority::Priority,
+    query::Query,
+    recur::parse_recurrence,
+    status::Status,
+    tags::{parse_tokens, with_tokens},
+    time::{format_duration, report, ReportBy},
+};
+use std::io::Write;
+
+#[derive(Debug, Parser)]
+#[command(version, about, before_help = BANNER)]
+pub struct Cli {
+    /// Runs a single command instead of opening the interactive UI.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Format used by `list`, `show` and `report`
+    #[arg(long, short, value_enum, global = true, default_value_t)]
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable
+    #[default]
+    Text,
+    /// Pretty-printed JSON array (a single object for `show`)
+    Json,
+    /// One compact JSON object per line
+    Ndjson,
+    /// Tab separated with a header row, columns in a fixed order
+    Tsv,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add a new task
+    Add {
+        /// `+project`, `@context` and `#tag` words set the project and tags
+        description: String,
+        #[arg(long)]
+        body: Option<String>,
+        /// Due date, e.g. `tomorrow`, `fri 14:00`, `+3d` or `2025-06-30`
+        #[arg(long)]
+        due: Option<String>,
+        /// none, low, medium, high or urgent
+        #[arg(long, short)]
+        priority: Option<Priority>,
+        /// Id of the task this one is a subtask of
+        #[arg(long)]
+        parent: Option<usize>,
+        /// Id of a task that has to be done first; can be repeated
+        #[arg(long)]
+        blocked_by: Vec<usize>,
+        /// `daily`, `weekly mon,thu`, `monthly 15` or `after 3d`
+        #[arg(long)]
+        repeat: Option<String>,
+    },
+    /// List tasks
+    List {
+        /// Include completed tasks
+        #[arg(long, short)]
+        all: bool,
+        /// Order of the listed tasks
+        #[arg(long, short, value_enum, default_value_t)]
+        sort: SortMode,
+        /// List in the opposite direction of the sort
+        #[arg(long, short)]
+        reverse: bool,
+        /// Only tasks with this tag; repeat to accept any of several
+        #[arg(long, short)]
+        tag: Vec<String>,
+        /// Only tasks in this project
+        #[arg(long, short = 'P')]
+        project: Option<String>,
+        /// Timestamp `--since` and `--until` look at: created, updated or
+        /// completed
+        #[arg(long)]
+        by: Option<Stamp>,
+        /// Only tasks stamped on or after this day, e.g. `-7d` or `2025-06-01`
+        #[arg(long, requires = "by", allow_hyphen_values = true)]
+        since: Option<String>,
+        /// Only tasks stamped on or before this day
+        #[arg(long, requires = "by", allow_hyphen_values = true)]
+        until: Option<String>,
+        /// Only tasks matching a query, e.g. `status:todo tag:work due<7d`
+        #[arg(long, allow_hyphen_values = true)]
+        filter: Option<String>,
+    },
+    /// Mark a task as done
+    Done {
+        id: usize,
+        /// Also mark its subtasks
+        #[arg(long, short)]
+        cascade: bool,
+    },
+    /// Mark a task as not done
+    Undo {
+        id: usize,
+        /// Also mark its subtasks
+        #[arg(long, short)]
+        cascade: bool,
+    },
+    /// Change the description, body, due date, priority or status of a task
+    Edit {
+        id: usize,
+        /// New description; `+project`, `@context` and `#tag` words are added
+        /// to the task instead
+        #[arg(long, short)]
+        description: Option<String>,
+        #[arg(long, short)]
+        body: Option<String>,
+        /// New due date; an empty value clears it
+        #[arg(long)]
+        due: Option<String>,
+        #[arg(long, short)]
+        priority: Option<Priority>,
+        /// todo, in-progress, waiting, done or cancelled
+        #[arg(long, short)]
+        status: Option<Status>,
+        /// Make it a subtask of this task
+        #[arg(long, conflicts_with = "root")]
+        parent: Option<usize>,
+        /// Make it a top level task again
+        #[arg(long)]
+        root: bool,
+        /// Add a task that has to be done first; can be repeated
+        #[arg(long)]
+        blocked_by: Vec<usize>,
+        /// Stop waiting on a task; can be repeated
+        #[arg(long)]
+        unblock: Vec<usize>,
+        /// New recurrence rule; an empty value stops repeating
+        #[arg(long)]
+        repeat: Option<String>,
+    },
+    /// Remove a task; its subtasks move up a level
+    Rm { id: usize },
+    /// Start tracking time on a task, stopping any other running timer
+    Start { id: usize },
+    /// Stop the running timer
+    Stop {
+        /// Only stop it if it runs on this task
+        id: Option<usize>,
+    },
+    /// Total tracked time
+    Report {
+        /// Add up per task, tag or day
+        #[arg(long, default_value_t)]
+        by: ReportBy,
+        /// Only time tracked on or after this day, e.g. `-7d` or `2025-06-01`
+        #[arg(long, allow_hyphen_values = true)]
+        since: Option<String>,
+        /// Only time tracked on or before this day
+        #[arg(long, allow_hyphen_values = true)]
+        until: Option<String>,
+    },
+    /// Show every field of a task
+    Show { id: usize },
+}
+
+/// Runs `command` against `app`, writing what it reports to `out`.
+///
+/// A write that conflicts with another process fails the command with
+/// `TaskError::Conflict`; nothing is printed as if it had succeeded.
+pub fn run(
+    command: Command,
+    format: OutputFormat,
+    app: &mut App,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    match command {
+        Command::Add {
+            description,
+            body,
+            due,
+            priority,
+            parent,
+            blocked_by,
+            repeat,
+        } => {
+            let tokens = parse_tokens(&description);
+            let mut task = Task::from_description(&tokens.description)?;
+            task.set_tags(tokens.tags);
+            task.set_project(tokens.project);
+            if let Some(body) = body {
+                task.set_body(body);
+            }
+            if let Some(due) = due {
+                task.set_due(parse_due(&due, now())?);
+            }
+            if let Some(priority) = priority {
+                task.set_priority(priority);
+            }
+            if let Some(parent) = parent {
+                app.position(parent)?;
+                task.set_parent(Some(parent));
+            }
+            // a new task can't close a cycle, the ids only have to exist
+            for &id in &blocked_by {
+                app.position(id)?;
+            }
+            task.set_blocked_by(blocked_by.into_iter().collect());
+            if let Some(rule) = repeat {
+                task.set_recurrence(parse_recurrence(&rule)?);
+            }
+            app.add_task(task)?;
+            let task = app.tasks.last().expect("task was just added");
+            writeln!(out, "Added task {}", task.get_id())?;
+        }
+        Command::List {
+            all,
+            sort,
+            reverse,
+            tag,
+            project,
+            by,
+            since,
+            until,
+            filter,
+        } => {
+            app.show_done = all;
+            app.sort = sort;
+            app.sort_reversed = reverse;
+            let query = filter
+                .map(|filter| Query::parse(&filter, now()))
+                .transpose()?;
+            app.filter = Filter {
+                tags: tag.into_iter().collect(),
+                project,
+                query: query.filter(|query| !query.is_empty()),
+                ..Default::default()
+            };
+            let since = since.map(|day| parse_day(&day)).transpose()?;
+            let until = until.map(|day| parse_day(&day)).transpose()?;
+            let tasks: Vec<&Task> = app
+                .view()
+                .into_iter()
+                .map(|i| &app.tasks[i])
+                .filter(|task| {
+                    let Some(stamp) = by else {
+                        return true;
+                    };
+                    task.stamp(stamp).is_some_and(|at| {
+                        let day = at.date_naive();
+                        since.is_none_or(|since| day >= since)
+                            && until.is_none_or(|until| day <= until)
+                    })
+                })
+                .collect();
+            print_tasks(&tasks, format, out)?;
+        }
+        Command::Done { id, cascade } => {
+            app.cascade = cascade;
+            let count = app.tasks.len();
+            app.set_task_done(id, true)?;
+            for task in &app.tasks[count..] {
+                writeln!(out, "Added next occurrence as task {}", task.get_id())?;
+            }
+        }
+        Command::Undo { id, cascade } => {
+            app.cascade = cascade;
+            app.set_task_done(id, false)?;
+        }
+        Command::Edit {
+            id,
+            description,
+            body,
+            due,
+            priority,
+            status,
+            parent,
+            root,
+            blocked_by,
+            unblock,
+            repeat,
+        } => {
+            let index = app.position(id)?;
+            if let Some(description) = description {
+                let tokens = parse_tokens(&description);
+                app.change_task_description(id, tokens.description)?;
+                if tokens.project.is_some() || !tokens.tags.is_empty() {
+                    let task = &app.tasks[index];
+                    let mut tags = task.tags().clone();
+                    tags.extend(tokens.tags);
+                    let project = tokens.project.or(task.project().map(str::to_owned));
+                    app.change_task_tags(id, tags, project)?;
+                }
+            }
+            if let Some(body) = body {
+                app.change_task_body(id, body)?;
+            }
+            if let Some(due) = due {
+                app.change_task_due(id, parse_due(&due, now())?)?;
+            }
+            if let Some(priority) = priority {
+                app.change_task_priority(id, priority)?;
+            }
+            if let Some(status) = status {
+                app.change_task_status(id, status)?;
+            }
+            if parent.is_some() || root {
+                app.change_task_parent(id, parent)?;
+            }
+            if !blocked_by.is_empty() || !unblock.is_empty() {
+                let mut ids = app.tasks[index].blocked_by().clone();
+                ids.extend(blocked_by);
+                ids.retain(|id| !unblock.contains(id));
+                app.change_task_blockers(id, ids)?;
+            }
+            if let Some(rule) = repeat {
+                app.change_task_recurrence(id, parse_recurrence(&rule)?)?;
+            }
+        }
+        Command::Rm { id } => {
+            app.remove_task(id)?;
+        }
+        Command::Start { id } => {
+            if !app.tasks[app.position(id)?].is_tracking() {
+                app.toggle_timer(id)?;
+            }
+            writeln!(out, "Tracking time on task {}", id)?;
+        }
+        Command::Stop { id } => {
+            let running = app
+                .tasks
+                .iter()
+                .find(|task| task.is_tracking() && id.is_none_or(|id| task.get_id() == id))
+                .map(Task::get_id);
+            match running {
+                Some(id) => {
+                    app.toggle_timer(id)?;
+                    let task = &app.tasks[app.position(id)?];
+                    let entry = task.time_entries().last().expect("timer was just stopped");
+                    writeln!(
+                        out,
+                        "Stopped task {} after {}",
+                        id,
+                        format_duration(entry.duration(Local::now()))
+                    )?;
+                }
+                None => writeln!(out, "No timer is running")?,
+            }
+        }
+        Command::Report { by, since, until } => {
+            let since = since.map(|day| parse_day(&day)).transpose()?;
+            let until = until.map(|day| parse_day(&day)).transpose()?;
+            let rows = report(&app.tasks, by, since, until, Local::now());
+            print_report(by, &rows, format, out)?;
+        }
+        Command::Show { id } => {
+            let task = &app.tasks[app.position(id)?];
+            match format {
+                OutputFormat::Text => {
+                    writeln!(out, "{}", summary(task))?;
+                    for blocker in app.blockers(app.position(id)?) {
+                        let blocker = &app.tasks[blocker];
+                        writeln!(
+                            out,
+                            "     blocked by {} {}",
+                            blocker.get_id(),
+                            blocker.description()
+                        )?;
+                    }
+                    if !task.time_entries().is_empty() {
+                        let tracked = format_duration(task.tracked(Local::now()));
+                        writeln!(out, "     {:<10}{}", "tracked", tracked)?;
+                    }
+                    for stamp in Stamp::ALL {
+                        if let Some(at) = task.stamp(stamp) {
+                            writeln!(out, "     {:<10}{}", stamp, at.format("%Y-%m-%d %H:%M"))?;
+                        }
+                    }
+                    if !task.body().is_empty() {
+                        writeln!(out)?;
+                        writeln!(out, "{}", task.body())?;
+                    }
+                }
+                OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(task)?)?,
+                _ => print_tasks(&[task], format, out)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Column order of the TSV output. New columns are only ever appended.
+const TSV_COLUMNS: [&str; 16] = [
+    "id",
+    "completed",
+    "description",
+    "body",
+    "due",
+    "priority",
+    "tags",
+    "project",
+    "parent",
+    "blocked_by",
+    "recurrence",
+    "created_at",
+    "updated_at",
+    "completed_at",
+    "status",
+    "time_entries",
+];
+
+fn print_tasks(
+    tasks: &[&Task],
+    format: OutputFormat,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for task in tasks {
+                writeln!(out, "{}", summary(task))?;
+            }
+        }
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(tasks)?)?,
+        OutputFormat::Ndjson => {
+            for task in tasks {
+                writeln!(out, "{}", serde_json::to_string(task)?)?;
+            }
+        }
+        OutputFormat::Tsv => {
+            writeln!(out, "{}", TSV_COLUMNS.join("\t"))?;
+            for task in tasks {
+                let row = [
+                    task.get_id().to_string(),
+                    task.completed().to_string(),
+                    escape(&task.description()),
+                    escape(&task.body()),
+                    task.due().map(|due| due.to_string()).unwrap_or_default(),
+                    task.priority().to_string(),
+                    escape(&tags(task)),
+                    escape(task.project().unwrap_or_default()),
+                    task.parent().map(|id| id.to_string()).unwrap_or_default(),
+                    blocked_by(task),
+                    task.recurrence()
+                        .map(|recurrence| recurrence.to_string())
+                        .unwrap_or_default(),
+                    stamp(task, Stamp::Created),
+                    stamp(task, Stamp::Updated),
+                    stamp(task, Stamp::Completed),
+                    task.status().to_string(),
+                    time_entries(task),
+                ];
+                writeln!(out, "{}", row.join("\t"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Report rows as `h:mm:ss` and name; in the structured formats the time is
+/// in whole seconds and the name column is named after `by`.
+fn print_report(
+    by: ReportBy,
+    rows: &[(String, Duration)],
+    format: OutputFormat,
+    out: &mut impl Write,
+) -> color_eyre::Result<()> {
+    let objects = || {
+        rows.iter().map(|(key, spent)| {
+            let mut object = serde_json::Map::new();
+            object.insert(by.to_string(), key.as_str().into());
+            object.insert(String::from("seconds"), spent.num_seconds().into());
+            object
+        })
+    };
+    match format {
+        OutputFormat::Text => {
+            for (key, spent) in rows {
+                writeln!(out, "{:>10}  {}", format_duration(*spent), key)?;
+            }
+        }
+        OutputFormat::Json => {
+            let objects: Vec<_> = objects().collect();
+            writeln!(out, "{}", serde_json::to_string_pretty(&objects)?)?;
+        }
+        OutputFormat::Ndjson => {
+            for object in objects() {
+                writeln!(out, "{}", serde_json::to_string(&object)?)?;
+            }
+        }
+        OutputFormat::Tsv => {
+            writeln!(out, "{}\tseconds", by)?;
+            for (key, spent) in rows {
+                writeln!(out, "{}\t{}", escape(key), spent.num_seconds())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn summary(task: &Task) -> String {
+    let mut line = format!("{:>4} {:<3} ", task.get_id(), task.status().marker());
+    if !task.priority().is_none() {
+        line.push_str(&format!("{} ", task.priority().marker()));
+    }
+    line.push_str(&with_tokens(task));
+    if let Some(due) = task.due() {
+        line.push_str(&format!("  ({}, {})", due, due.relative(now())));
+    }
+    line
+}
+
+/// Day given to `--since` or `--until`, in any form a due date accepts.
+fn parse_day(input: &str) -> Result<NaiveDate, TaskError> {
+    let due = parse_due(input, now())?.ok_or(TaskError::Validation {
+        field: "date",
+        reason: String::from("text is empty"),
+    })?;
+    Ok(due.date())
+}
+
+fn now() -> NaiveDateTime {
+    Local::now().naive_local()
+}
@@ -1,3 +1,4 @@
 pub mod app;
 pub mod banner;
+pub mod cli;
 pub mod ui;
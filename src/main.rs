@@ -1,32 +1,71 @@
+use clap::Parser;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use model::util::get_data_path;
+use model::{config::Config, store::SqliteStore, util::get_data_path};
 use ratatui::{prelude::CrosstermBackend, Terminal};
 use std::{env, io};
-use todo_app::app::App;
+use todo_app::{
+    app::App,
+    cli::{self, Cli},
+};
 
 pub mod app;
 pub mod banner;
 pub mod ui;
 
 const DEFAULT_DATA_FILENAME: &str = "data.json";
+const DEFAULT_CONFIG_FILENAME: &str = "config.json";
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
-    enable_raw_mode()?;
+    let args = Cli::parse();
 
     let filename = env::var("JSON_FILE").unwrap_or_else(|_| DEFAULT_DATA_FILENAME.to_string());
-    let json_path = get_data_path(&filename).expect("could not get data directory");
+    let data_path = get_data_path(&filename).expect("could not get data directory");
+
+    // opened before touching the terminal so load errors are printed normally
+    let app = if SqliteStore::is_sqlite_path(&data_path) {
+        App::with_sqlite(data_path)
+    } else {
+        App::with_json(data_path)
+    };
+    let mut app = app?;
+
+    let filename = env::var("CONFIG_FILE").unwrap_or_else(|_| DEFAULT_CONFIG_FILENAME.to_string());
+    let config_path = get_data_path(&filename).expect("could not get data directory");
+    app.config = Config::load(&config_path)?;
+    app.config_path = Some(config_path);
+    let warnings: Vec<String> = app
+        .config
+        .view_errors()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    if let Some(command) = args.command {
+        for warning in &warnings {
+            eprintln!("warning: {}", warning);
+        }
+        return cli::run(command, args.output, &mut app, &mut io::stdout());
+    }
+    if !warnings.is_empty() {
+        app.status = Some(warnings.join("; "));
+    }
+
+    if let Err(err) = app.watch() {
+        app.status = Some(format!("Live reload disabled: {}", err));
+    }
+
+    enable_raw_mode()?;
 
     let mut stderr = io::stderr();
     execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
 
     let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
-    let mut app = App::with_json(json_path).unwrap_or_default();
     let result = app.run(&mut terminal);
 
     if let Err(err) = ratatui::try_restore() {
@@ -1,12 +1,18 @@
-use crate::app::{App, CurrentScreen};
+use crate::app::{App, CurrentScreen, PickerEntry, View};
 use crate::banner::BANNER;
+use chrono::{Local, NaiveDateTime};
+use model::common::{Stamp, Task};
+use model::priority::Priority;
+use model::search::SearchHit;
+use model::status::Status;
+use model::time::format_duration;
 use model::util::is_completed;
 use ratatui::layout::{Constraint, Direction, Flex, Layout, Position, Rect};
 use ratatui::prelude::Stylize;
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::symbols::{self, border};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Borders, Clear, List, Padding, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Clear, List, ListState, Padding, Paragraph, Wrap};
 use ratatui::Frame;
 
 pub fn render(app: &mut App, frame: &mut Frame) {
@@ -24,20 +30,59 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         .constraints([Constraint::Percentage(80), Constraint::Percentage(20)])
         .split(chunks[1]);
 
+    // views sidebar on top of the details, taking at most half the panel
+    let views = app.views();
+    let sidebar_height = (views.len() as u16 + 2).min(content_chunk[1].height / 2);
+    let side_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(sidebar_height), Constraint::Min(1)])
+        .split(content_chunk[1]);
+
+    let view_items: Vec<Line> = views
+        .iter()
+        .enumerate()
+        .map(|(row, view)| {
+            let key = match row {
+                0..9 => format!("{} ", row + 1),
+                _ => String::from("  "),
+            };
+            Line::from(vec![
+                Span::raw(key).fg(Color::DarkGray),
+                Span::raw(view.label()),
+                Span::raw(format!(" {}", app.view_count(view))).fg(Color::Gray),
+            ])
+        })
+        .collect();
+    let mut sidebar_state =
+        ListState::default().with_selected(views.iter().position(|view| *view == app.active_view));
+    let sidebar = List::new(view_items)
+        .block(
+            Block::bordered()
+                .border_set(symbols::border::ROUNDED)
+                .title(" Views "),
+        )
+        .highlight_style(Style::new().reversed());
+    frame.render_stateful_widget(sidebar, side_chunks[0], &mut sidebar_state);
+
     let info_block = Block::bordered()
         .border_set(symbols::border::ROUNDED)
         .title_bottom(format!(" {} ", env!("CARGO_PKG_VERSION")))
         .title_alignment(ratatui::layout::Alignment::Right);
 
-    let info_text = Paragraph::new(
-        Text::from("Thank you for using Tasks!")
-            .bold()
-            .style(Style::default()),
-    )
-    .block(info_block)
-    .centered();
+    let info_text = match app.selected() {
+        Some(index) => Paragraph::new(details(app, index))
+            .block(info_block)
+            .wrap(Wrap { trim: false }),
+        None => Paragraph::new(
+            Text::from("Thank you for using Tasks!")
+                .bold()
+                .style(Style::default()),
+        )
+        .block(info_block)
+        .centered(),
+    };
 
-    frame.render_widget(info_text, content_chunk[1]);
+    frame.render_widget(info_text, side_chunks[1]);
 
     let throbber_widget = throbber_widgets_tui::Throbber::default()
         .throbber_style(
@@ -48,40 +93,94 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         .throbber_set(throbber_widgets_tui::BRAILLE_SIX)
         .use_type(throbber_widgets_tui::WhichUse::Spin);
 
-    if let true = app.loading {
+    if app.loading {
         frame.render_stateful_widget(throbber_widget, chunks[0], &mut app.throbber_state);
+    } else if let Some(status) = &app.status {
+        frame.render_widget(
+            Paragraph::new(Line::from(format!(" {} ", status)).fg(Color::LightYellow)),
+            chunks[0],
+        );
     }
 
-    let items: Vec<String> = if app.show_done {
-        app.tasks
-            .iter()
-            .map(|task| {
-                is_completed(task.completed()).as_str().to_owned()
-                    + " "
-                    + task.description().as_str()
-            })
-            .collect::<Vec<String>>()
-    } else {
-        app.tasks
-            .iter()
-            .filter(|x| x.completed() == false)
-            .map(|task| {
-                is_completed(task.completed()).as_str().to_owned()
-                    + " "
-                    + task.description().as_str()
-            })
-            .collect::<Vec<String>>()
-    };
+    let now = Local::now().naive_local();
+    let links = app.links();
+    let items: Vec<Line> = app
+        .tree()
+        .into_iter()
+        .map(|(index, depth)| {
+            let task = &app.tasks[index];
+            let progress = links.progress(index);
+            let fold = match progress {
+                None => "  ",
+                Some(_) if app.collapsed.contains(&task.get_id()) => "▸ ",
+                Some(_) => "▾ ",
+            };
+            let mut line = task_line(task, now, app.search_hit(index));
+            if !links.blockers(index).is_empty() {
+                line = line.patch_style(Modifier::DIM);
+            }
+            line.spans.insert(0, Span::raw("  ".repeat(depth) + fold));
+            if let Some((done, total)) = progress {
+                line.push_span(Span::raw(format!("  {}/{}", done, total)).fg(Color::Gray));
+            }
+            if task.recurrence().is_some() {
+                line.push_span(Span::raw("  ↻").fg(Color::Gray));
+            }
+            if task.is_tracking() {
+                let elapsed = format_duration(task.tracked(app.clock));
+                line.push_span(Span::raw(format!("  ⏱ {}", elapsed)).fg(Color::LightRed));
+            }
+            line
+        })
+        .collect();
 
     let footer_text = {
         match app.current_screen {
+            CurrentScreen::Main if !app.search.is_empty() => Paragraph::new(Line::from(vec![
+                " Next Match ".into(),
+                "<n>".red().bold(),
+                " Previous Match ".into(),
+                "<N>".red().bold(),
+                " Search Again ".into(),
+                "</>".red().bold(),
+                " Clear Search ".into(),
+                "<Esc> ".red().bold(),
+            ])),
             CurrentScreen::Main => Paragraph::new(Line::from(vec![
                 " New Entry ".into(),
                 "<N>".red().bold(),
                 " Edit ".into(),
                 "<Enter>".red().bold(),
-                " Mark as Done ".into(),
+                " Status ".into(),
                 "<Tab>".red().bold(),
+                " Priority ".into(),
+                "<+/->".red().bold(),
+                " Sort ".into(),
+                "<S>".red().bold(),
+                " Reverse ".into(),
+                "<R>".red().bold(),
+                " Move ".into(),
+                "<J/K>".red().bold(),
+                " Filter ".into(),
+                "<F>".red().bold(),
+                " Views ".into(),
+                "<[/]>".red().bold(),
+                " Save View ".into(),
+                "<V>".red().bold(),
+                " Search ".into(),
+                "</>".red().bold(),
+                " Query ".into(),
+                "<:>".red().bold(),
+                " Indent ".into(),
+                "<>>".red().bold(),
+                " Outdent ".into(),
+                "<<>".red().bold(),
+                " Fold ".into(),
+                "<←/→>".red().bold(),
+                " Timer ".into(),
+                "<T>".red().bold(),
+                " Cascade ".into(),
+                "<C>".red().bold(),
                 " Delete ".into(),
                 "<Del>".red().bold(),
                 " Show/Hide ".into(),
@@ -94,7 +193,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
             CurrentScreen::Editing => Paragraph::new(Line::from(vec![
                 " Done Editing ".into(),
                 "<Enter> ".red().bold(),
-                " Description/Body ".into(),
+                " Next Field ".into(),
                 "<Tab>".red().bold(),
                 " Back ".into(),
                 "<Esc> ".red().bold(),
@@ -111,22 +210,105 @@ pub fn render(app: &mut App, frame: &mut Frame) {
             CurrentScreen::Deleting => {
                 Paragraph::new(Line::from(vec![" Delete ".into(), "<Y/N>".red().bold()]))
             }
+            CurrentScreen::Picker => Paragraph::new(Line::from(vec![
+                " Toggle ".into(),
+                "<Enter/Space>".red().bold(),
+                " Clear ".into(),
+                "<C>".red().bold(),
+                " Back ".into(),
+                "<Esc> ".red().bold(),
+            ])),
+            CurrentScreen::Search => Paragraph::new(Line::from(vec![
+                " /".red().bold(),
+                Span::raw(app.search.clone()),
+                "  Matches ".into(),
+                "<↑/↓>".red().bold(),
+                " Keep ".into(),
+                "<Enter>".red().bold(),
+                " Clear ".into(),
+                "<Esc> ".red().bold(),
+            ])),
+            CurrentScreen::Query => Paragraph::new(Line::from(vec![
+                " :".red().bold(),
+                Span::raw(app.buffer.clone()),
+                "  Apply ".into(),
+                "<Enter>".red().bold(),
+                " Cancel ".into(),
+                "<Esc> ".red().bold(),
+            ])),
+            CurrentScreen::Conflict => Paragraph::new(Line::from(vec![
+                " Reload ".into(),
+                "<R>".red().bold(),
+                " Keep Mine ".into(),
+                "<K>".red().bold(),
+                " Later ".into(),
+                "<Esc> ".red().bold(),
+            ])),
         }
     };
 
     frame.render_widget(footer_text, chunks[2]);
+    if let CurrentScreen::Search = app.current_screen {
+        let typed = app.search.chars().count() as u16;
+        frame.set_cursor_position(Position::new(chunks[2].x + 2 + typed, chunks[2].y));
+    }
+    if let CurrentScreen::Query = app.current_screen {
+        let cursor = app.character_index as u16;
+        frame.set_cursor_position(Position::new(chunks[2].x + 2 + cursor, chunks[2].y));
+    }
 
+    let arrow = if app.sort.descending() != app.sort_reversed {
+        "↓"
+    } else {
+        "↑"
+    };
+    let mut list_title = Line::from(format!(" by {} {} ", app.sort.label(), arrow)).fg(Color::Gray);
+    if app.active_view != View::All {
+        list_title.push_span(Span::raw(" "));
+        list_title.push_span(Span::styled(
+            format!(" {} ", app.active_view.label()),
+            Style::default().fg(Color::Black).bg(Color::White),
+        ));
+    }
+    if !app.filter.is_empty() {
+        list_title.push_span(Span::raw(" "));
+        if let Some(project) = &app.filter.project {
+            list_title.push_span(project_chip(project));
+        }
+        for tag in &app.filter.tags {
+            list_title.push_span(tag_chip(tag));
+        }
+        if let Some((stamp, days)) = app.filter.recent {
+            list_title.push_span(recent_chip(stamp, days));
+        }
+        if let Some(query) = &app.filter.query {
+            list_title.push_span(Span::styled(
+                format!(" :{} ", query),
+                Style::default().fg(Color::Black).bg(Color::LightCyan),
+            ));
+        }
+    }
+    if !app.search.is_empty() {
+        let matches = app.search_rows().len();
+        list_title.push_span(Span::raw(" "));
+        list_title.push_span(Span::styled(
+            format!(" /{} {} of {} ", app.search, matches, app.view().len()),
+            Style::default().fg(Color::Black).bg(Color::LightYellow),
+        ));
+    }
     let list = List::new(items)
         .block(
             Block::bordered()
                 .border_set(border::ROUNDED)
-                .borders(Borders::ALL),
+                .borders(Borders::ALL)
+                .title(list_title),
         )
         .highlight_style(Style::new().reversed())
         .highlight_symbol(" >> ")
         .repeat_highlight_symbol(true)
         .highlight_spacing(ratatui::widgets::HighlightSpacing::WhenSelected);
 
+    app.list_area = content_chunk[0];
     frame.render_stateful_widget(list, content_chunk[0], &mut app.state);
 
     let nick = vec![
@@ -165,13 +347,19 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     if let CurrentScreen::Editing = app.current_screen {
         let title = match &app.current_editing {
             crate::app::CurrentEditing::Description => " ".to_owned() + &app.buffer + " ",
-            crate::app::CurrentEditing::Body => {
-                " ".to_owned() + &app.tasks[app.state.selected().unwrap()].description() + " "
-            }
+            _ => app
+                .selected()
+                .map(|index| " ".to_owned() + &app.tasks[index].description() + " ")
+                .unwrap_or_default(),
         };
 
         let popup_block = Block::default()
             .title(title)
+            .title_bottom(
+                Line::from(format!(" {} ", app.current_editing.label()))
+                    .right_aligned()
+                    .fg(Color::Gray),
+            )
             .title_style(Style::default().fg(Color::LightYellow))
             .borders(Borders::ALL)
             .border_set(symbols::border::ROUNDED)
@@ -234,6 +422,70 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         frame.render_widget(exit_paragraph, area);
     }
 
+    if let CurrentScreen::Conflict = app.current_screen {
+        let popup_block = Block::default()
+            .title_bottom(Line::from(" R/K ").right_aligned().fg(Color::LightYellow))
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .style(Style::default())
+            .padding(Padding::vertical(2));
+
+        let conflict_text = Text::styled(
+            "Tasks were changed by another process",
+            Style::default().bold().fg(Color::Red),
+        )
+        .alignment(ratatui::layout::Alignment::Center);
+
+        let conflict_paragraph = Paragraph::new(conflict_text.clone())
+            .block(popup_block)
+            .centered()
+            .wrap(Wrap { trim: false });
+
+        let area = center(
+            frame.area(),
+            Constraint::Length(conflict_text.width() as u16 + 4),
+            Constraint::Length(conflict_text.height() as u16 + 6),
+        );
+        frame.render_widget(Clear, frame.area());
+        frame.render_widget(conflict_paragraph, area);
+    }
+
+    if let CurrentScreen::Picker = app.current_screen {
+        let entries = app.picker_entries();
+        let items: Vec<Line> = entries
+            .iter()
+            .map(|entry| {
+                let check = is_completed(app.filter.contains(entry));
+                let chip = match entry {
+                    PickerEntry::Project(name) => project_chip(name),
+                    PickerEntry::Tag(name) => tag_chip(name),
+                    PickerEntry::Recent(stamp, days) => recent_chip(*stamp, *days),
+                };
+                Line::from(vec![Span::raw(format!("{:<4}", check)), chip])
+            })
+            .collect();
+        let height = entries.len() as u16 + 2;
+
+        let popup_block = Block::default()
+            .title(" Filter ")
+            .title_style(Style::default().fg(Color::LightYellow))
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .style(Style::default());
+
+        let picker = List::new(items)
+            .block(popup_block)
+            .highlight_style(Style::new().reversed());
+
+        let area = center(
+            frame.area(),
+            Constraint::Percentage(40),
+            Constraint::Length(height),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(picker, area, &mut app.picker);
+    }
+
     if let CurrentScreen::Exiting = app.current_screen {
         let popup_block = Block::default()
             .title_bottom(Line::from(" Y/N ").right_aligned().fg(Color::LightYellow))
@@ -262,6 +514,183 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         frame.render_widget(exit_paragraph, area);
     }
 }
+/// List entry for a task, marked with its status and priority and colored by
+/// how close it is to its due date. The characters matched by a search are
+/// highlighted.
+fn task_line(task: &Task, now: NaiveDateTime, hit: Option<SearchHit>) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled(task.status().marker(), status_style(task.status())),
+        Span::raw(" "),
+    ];
+    if !task.priority().is_none() {
+        spans.push(Span::styled(
+            format!("{} ", task.priority().marker()),
+            priority_style(task.priority()),
+        ));
+    }
+
+    let style = match (task.completed(), task.due()) {
+        (true, _) if task.status() == Status::Cancelled => Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::CROSSED_OUT),
+        (true, _) => Style::default().fg(Color::DarkGray),
+        (false, Some(due)) if due.is_overdue(now) => Style::default().fg(Color::Red),
+        (false, Some(due)) if due.is_due_today(now) => Style::default().fg(Color::Yellow),
+        _ => Style::default(),
+    };
+    match &hit {
+        Some(SearchHit::Description(positions)) => {
+            let matched = style.patch(search_style());
+            spans.extend(task.description().chars().enumerate().map(|(i, c)| {
+                let style = if positions.contains(&i) {
+                    matched
+                } else {
+                    style
+                };
+                Span::styled(c.to_string(), style)
+            }));
+        }
+        _ => spans.push(Span::styled(task.description(), style)),
+    }
+
+    if let Some(project) = task.project() {
+        spans.push(Span::raw(" "));
+        spans.push(project_chip(project));
+    }
+    for tag in task.tags() {
+        spans.push(Span::raw(" "));
+        spans.push(tag_chip(tag));
+    }
+    if hit == Some(SearchHit::Body) {
+        spans.push(Span::styled("  (in body)", search_style()));
+    }
+    if let Some(due) = task.due() {
+        spans.push(Span::styled(
+            format!("  {}", due.relative(now)),
+            style.add_modifier(Modifier::DIM),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Fields of the selected task that don't fit in the list.
+fn details(app: &App, index: usize) -> Text<'static> {
+    let task = &app.tasks[index];
+    let mut lines = vec![
+        Line::from(task.description()).bold(),
+        Line::from(vec![
+            Span::raw("Status "),
+            Span::styled(task.status().to_string(), status_style(task.status())),
+        ]),
+    ];
+    if let Some(due) = task.due() {
+        lines.push(Line::from(format!("Due {}", due)));
+    }
+    if let Some(recurrence) = task.recurrence() {
+        lines.push(Line::from(format!("Repeats {}", recurrence)));
+    }
+    if !task.priority().is_none() {
+        lines.push(Line::from(format!("Priority {}", task.priority())));
+    }
+    if let Some((done, total)) = app.progress(index) {
+        lines.push(Line::from(format!("Subtasks {}/{} done", done, total)));
+    }
+    if !task.time_entries().is_empty() {
+        let tracked = format!("Tracked {}", format_duration(task.tracked(app.clock)));
+        lines.push(if task.is_tracking() {
+            Line::from(tracked + " (running)").fg(Color::LightRed)
+        } else {
+            Line::from(tracked)
+        });
+    }
+
+    let blockers = app.blockers(index);
+    if !blockers.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::from("Blocked by").fg(Color::Red));
+        for i in blockers {
+            let blocker = &app.tasks[i];
+            lines.push(Line::from(format!(
+                "{:>3} {}",
+                blocker.get_id(),
+                blocker.description()
+            )));
+        }
+    }
+
+    if !task.body().is_empty() {
+        lines.push(Line::default());
+        lines.extend(task.body().lines().map(|line| Line::from(line.to_owned())));
+    }
+
+    let stamps: Vec<Line> = Stamp::ALL
+        .into_iter()
+        .filter_map(|stamp| {
+            let at = task.stamp(stamp)?;
+            Some(
+                Line::from(format!("{:<10}{}", stamp, at.format("%Y-%m-%d %H:%M"))).fg(Color::Gray),
+            )
+        })
+        .collect();
+    if !stamps.is_empty() {
+        lines.push(Line::default());
+        lines.extend(stamps);
+    }
+    Text::from(lines)
+}
+
+fn recent_chip(stamp: Stamp, days: u32) -> Span<'static> {
+    let period = match days {
+        1 => String::from("today"),
+        days => format!("in {} days", days),
+    };
+    Span::styled(
+        format!(" {} {} ", stamp, period),
+        Style::default().fg(Color::Black).bg(Color::LightMagenta),
+    )
+}
+
+fn project_chip(name: &str) -> Span<'static> {
+    Span::styled(
+        format!(" +{} ", name),
+        Style::default().fg(Color::Black).bg(Color::LightBlue),
+    )
+}
+
+fn tag_chip(name: &str) -> Span<'static> {
+    Span::styled(
+        format!(" #{} ", name),
+        Style::default().fg(Color::Black).bg(Color::LightGreen),
+    )
+}
+
+fn search_style() -> Style {
+    Style::default()
+        .fg(Color::LightYellow)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+}
+
+fn status_style(status: Status) -> Style {
+    let color = match status {
+        Status::Todo => Color::Reset,
+        Status::InProgress => Color::Yellow,
+        Status::Waiting => Color::Magenta,
+        Status::Done => Color::Green,
+        Status::Cancelled => Color::DarkGray,
+    };
+    Style::default().fg(color)
+}
+
+fn priority_style(priority: Priority) -> Style {
+    let color = match priority {
+        Priority::None | Priority::Low => Color::Blue,
+        Priority::Medium => Color::Cyan,
+        Priority::High => Color::Magenta,
+        Priority::Urgent => Color::Red,
+    };
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
 fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
     let [area] = Layout::horizontal([horizontal])
         .flex(Flex::Center)
@@ -0,0 +1,233 @@
+mod common;
+
+use chrono::{Duration, Local, TimeZone};
+use clap::Parser;
+use common::setup_memory_app;
+use model::common::Stamp;
+use model::time::TimeEntry;
+use todo_app::cli::{run, Cli, Command};
+
+fn exec(app: &mut todo_app::app::App, args: &[&str]) -> String {
+    let cli = Cli::try_parse_from([&["todo-app"], args].concat()).expect("invalid arguments");
+    let mut out = Vec::new();
+    run(
+        cli.command.expect("missing command"),
+        cli.output,
+        app,
+        &mut out,
+    )
+    .expect("command failed");
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn add_done_and_list() {
+    let mut app = setup_memory_app();
+    assert_eq!(
+        exec(&mut app, &["add", "write report", "--body", "by friday"]),
+        "Added task 0\n"
+    );
+    exec(&mut app, &["add", "second"]);
+    exec(&mut app, &["done", "0"]);
+
+    assert_eq!(exec(&mut app, &["list"]), "   1 [ ] second\n");
+    assert_eq!(
+        exec(&mut app, &["list", "--all"]),
+        "   0 [x] write report\n   1 [ ] second\n"
+    );
+    assert_eq!(app.tasks[0].body(), "by friday");
+}
+
+#[test]
+fn unknown_id_is_an_error() {
+    let mut app = setup_memory_app();
+    let cli = Cli::try_parse_from(["todo-app", "rm", "7"]).unwrap();
+    assert!(run(cli.command.unwrap(), cli.output, &mut app, &mut Vec::new()).is_err());
+}
+
+#[test]
+fn no_subcommand_opens_tui() {
+    let cli = Cli::try_parse_from(["todo-app"]).unwrap();
+    assert!(cli.command.is_none());
+    assert!(matches!(
+        Cli::try_parse_from(["todo-app", "edit", "3", "-d", "new"])
+            .unwrap()
+            .command,
+        Some(Command::Edit { id: 3, .. })
+    ));
+}
+
+#[test]
+fn structured_output() {
+    let mut app = setup_memory_app();
+    exec(&mut app, &["add", "tab\there", "--body", "two\nlines"]);
+
+    let tsv = exec(&mut app, &["list", "--output", "tsv"]);
+    let (header, row) = tsv.split_once('\n').unwrap();
+    assert_eq!(
+        header,
+        "id\tcompleted\tdescription\tbody\tdue\tpriority\ttags\tproject\tparent\tblocked_by\trecurrence\tcreated_at\tupdated_at\tcompleted_at\tstatus\ttime_entries"
+    );
+    let row: Vec<&str> = row.trim_end_matches('\n').split('\t').collect();
+    assert_eq!(
+        row[..11],
+        [
+            "0",
+            "false",
+            "tab\\there",
+            "two\\nlines",
+            "",
+            "none",
+            "",
+            "",
+            "",
+            "",
+            ""
+        ]
+    );
+    assert!(!row[11].is_empty());
+    assert_eq!(row[13], "");
+    assert_eq!(row[14], "todo");
+    assert_eq!(row[15], "");
+
+    let ndjson = exec(&mut app, &["list", "-o", "ndjson"]);
+    let task: serde_json::Value = serde_json::from_str(ndjson.trim_end()).unwrap();
+    assert_eq!(task["description"], "tab\there");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&exec(&mut app, &["show", "0", "-o", "json"])).unwrap();
+    assert_eq!(json["body"], "two\nlines");
+}
+
+#[test]
+fn list_sorted_by_priority() {
+    let mut app = setup_memory_app();
+    exec(&mut app, &["add", "later"]);
+    exec(&mut app, &["add", "soon", "--due", "2025-06-30"]);
+    exec(&mut app, &["add", "sooner", "--due", "2025-06-01"]);
+    exec(&mut app, &["add", "now", "-p", "urgent"]);
+
+    assert_eq!(
+        exec(&mut app, &["list", "--sort", "priority", "-o", "tsv"])
+            .lines()
+            .skip(1)
+            .map(|row| row.split('\t').nth(2).unwrap())
+            .collect::<Vec<_>>(),
+        ["now", "sooner", "soon", "later"]
+    );
+    assert_eq!(app.tasks[0].description(), "later");
+    assert_eq!(
+        exec(&mut app, &["list", "--sort", "due", "-r", "-o", "tsv"])
+            .lines()
+            .skip(1)
+            .map(|row| row.split('\t').nth(2).unwrap())
+            .collect::<Vec<_>>(),
+        ["soon", "sooner", "later", "now"]
+    );
+}
+
+#[test]
+fn tags_from_description_and_list_filter() {
+    let mut app = setup_memory_app();
+    exec(&mut app, &["add", "buy milk +home #errand"]);
+    exec(&mut app, &["add", "send invoice +work"]);
+    exec(&mut app, &["edit", "1", "-d", "send the invoice #mail"]);
+
+    assert_eq!(app.tasks[1].description(), "send the invoice");
+    assert_eq!(app.tasks[1].project(), Some("work"));
+    assert_eq!(
+        exec(&mut app, &["list", "--tag", "mail", "--tag", "errand"]),
+        "   0 [ ] buy milk +home #errand\n   1 [ ] send the invoice +work #mail\n"
+    );
+    assert_eq!(
+        exec(&mut app, &["list", "-P", "work"]),
+        "   1 [ ] send the invoice +work #mail\n"
+    );
+    assert_eq!(
+        exec(&mut app, &["list", "--filter", "-#mail status:todo"]),
+        "   0 [ ] buy milk +home #errand\n"
+    );
+}
+
+#[test]
+fn list_by_completion_time() {
+    let mut app = setup_memory_app();
+    exec(&mut app, &["add", "old"]);
+    exec(&mut app, &["add", "fresh"]);
+    exec(&mut app, &["done", "1"]);
+
+    assert_eq!(
+        exec(
+            &mut app,
+            &["list", "--all", "--by", "completed", "--since", "yesterday"]
+        ),
+        "   1 [x] fresh\n"
+    );
+    assert_eq!(
+        exec(
+            &mut app,
+            &["list", "--all", "--by", "completed", "--until", "-1d"]
+        ),
+        ""
+    );
+    assert!(Cli::try_parse_from(["todo-app", "list", "--since", "-7d"]).is_err());
+}
+
+#[test]
+fn start_stop_and_report() {
+    let mut app = setup_memory_app();
+    exec(&mut app, &["add", "write +docs"]);
+    assert_eq!(exec(&mut app, &["stop"]), "No timer is running\n");
+    assert_eq!(exec(&mut app, &["start", "0"]), "Tracking time on task 0\n");
+    assert!(app.tasks[0].is_tracking());
+    assert!(exec(&mut app, &["stop"]).starts_with("Stopped task 0 after 0:00:0"));
+
+    let entry = app.tasks[0].time_entries()[0].clone();
+    app.tasks[0].set_time_entries(vec![TimeEntry {
+        end: Some(entry.start + Duration::minutes(90)),
+        ..entry
+    }]);
+    assert_eq!(
+        exec(&mut app, &["report", "--by", "tag", "-o", "tsv"]),
+        "tag\tseconds\nuntagged\t5400\n"
+    );
+    assert_eq!(exec(&mut app, &["report"]), "   1:30:00  0 write\n");
+}
+
+#[test]
+fn conflicting_write_fails_the_command() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+    let mut app = todo_app::app::App::with_json(path.clone()).unwrap();
+    let mut other = todo_app::app::App::with_json(path.clone()).unwrap();
+    exec(&mut other, &["add", "from elsewhere"]);
+
+    // main turns the error into a non-zero exit
+    let cli = Cli::try_parse_from(["todo-app", "add", "mine"]).unwrap();
+    let mut out = Vec::new();
+    let result = run(cli.command.unwrap(), cli.output, &mut app, &mut out);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("changed by another process"));
+    assert!(out.is_empty());
+
+    let on_disk = todo_app::app::App::with_json(path).unwrap();
+    assert_eq!(on_disk.tasks.len(), 1);
+    assert_eq!(on_disk.tasks[0].description(), "from elsewhere");
+}
+
+#[test]
+fn show_pads_timestamp_labels() {
+    let mut app = setup_memory_app();
+    exec(&mut app, &["add", "stamped"]);
+    let at = Local.with_ymd_and_hms(2026, 10, 18, 9, 5, 0).unwrap();
+    for stamp in Stamp::ALL {
+        app.tasks[0].set_stamp(stamp, Some(at));
+    }
+
+    let shown = exec(&mut app, &["show", "0"]);
+    assert!(shown.contains(
+        "     created   2026-10-18 09:05\n     updated   2026-10-18 09:05\n     completed 2026-10-18 09:05\n"
+    ));
+}
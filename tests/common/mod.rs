@@ -1,8 +1,10 @@
 use std::env;
 
+use model::store::MemoryStore;
 use tempfile::TempDir;
 use todo_app::app::App;
 
+#[allow(dead_code)]
 pub(crate) const DEFAULT_DATA_FILENAME: &str = "user_data";
 
 #[allow(dead_code)]
@@ -11,6 +13,7 @@ pub struct TestApp {
     pub app: App,
 }
 
+#[allow(dead_code)]
 pub fn setup_test_app() -> TestApp {
     let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
     let path = temp_dir
@@ -22,3 +25,8 @@ pub fn setup_test_app() -> TestApp {
 
     TestApp { temp_dir, app }
 }
+
+#[allow(dead_code)]
+pub fn setup_memory_app() -> App {
+    App::with_store(Box::new(MemoryStore::default())).expect("failed to instantiate app")
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::{Task, TaskError};
+use model::deps::find_cycle;
+use model::line::{decode, encode};
+use std::collections::BTreeSet;
+use todo_app::app::App;
+
+/// `deploy` waits on `test`, which waits on `build`.
+fn setup_chain() -> App {
+    let mut app = setup_memory_app();
+    for description in ["build", "test", "deploy"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.change_task_blockers(1, BTreeSet::from([0])).unwrap();
+    app.change_task_blockers(2, BTreeSet::from([1])).unwrap();
+    app
+}
+
+#[test]
+fn blocked_task_cannot_be_completed() {
+    let mut app = setup_chain();
+    assert_eq!(app.blockers(2), [1]);
+
+    let err = app.set_task_done(2, true).unwrap_err();
+    assert!(matches!(err, TaskError::Blocked { id: 2, ref by } if by == &[1]));
+    assert!(!app.tasks[2].completed());
+
+    app.set_task_done(0, true).unwrap();
+    app.set_task_done(1, true).unwrap();
+    assert!(app.blockers(2).is_empty());
+    app.set_task_done(2, true).unwrap();
+}
+
+#[test]
+fn cycles_are_rejected() {
+    let mut app = setup_chain();
+    assert_eq!(find_cycle(&app.tasks, 0, 2), Some(vec![0, 2, 1, 0]));
+
+    let err = app
+        .change_task_blockers(0, BTreeSet::from([2]))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "dependency cycle: 0 -> 2 -> 1 -> 0");
+    assert!(app.change_task_blockers(0, BTreeSet::from([0])).is_err());
+    assert!(app.tasks[0].blocked_by().is_empty());
+}
+
+#[test]
+fn removed_blocker_is_forgotten() {
+    let mut app = setup_chain();
+    app.remove_task(1).unwrap();
+    assert!(app.tasks[1].blocked_by().is_empty());
+}
+
+#[test]
+fn blocked_by_survives_line_format() {
+    let mut task = Task::from_description("deploy").unwrap();
+    task.set_blocked_by(BTreeSet::from([3, 12]));
+    assert_eq!(
+        decode(&encode(&task), 1).unwrap().blocked_by(),
+        task.blocked_by()
+    );
+}
@@ -0,0 +1,60 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use model::common::Task;
+use model::due::{parse_due, Due};
+use model::line::{decode, encode};
+
+// a Wednesday
+fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 6, 11)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap()
+}
+
+fn date(d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, d).unwrap()
+}
+
+#[test]
+fn parses_natural_forms() {
+    assert_eq!(
+        parse_due("tomorrow", now()).unwrap(),
+        Some(Due::Date(date(12)))
+    );
+    assert_eq!(parse_due("fri", now()).unwrap(), Some(Due::Date(date(13))));
+    assert_eq!(
+        parse_due("Wednesday", now()).unwrap(),
+        Some(Due::Date(date(18)))
+    );
+    assert_eq!(parse_due("+3d", now()).unwrap(), Some(Due::Date(date(14))));
+    assert_eq!(parse_due("+1w", now()).unwrap(), Some(Due::Date(date(18))));
+    assert_eq!(
+        parse_due("2025-06-30 14:30", now()).unwrap(),
+        Some(Due::DateTime(date(30).and_hms_opt(14, 30, 0).unwrap()))
+    );
+    assert_eq!(parse_due("", now()).unwrap(), None);
+    assert!(parse_due("someday", now()).is_err());
+}
+
+#[test]
+fn rejects_offsets_ending_in_other_characters() {
+    for input in ["+3é", "+é", "+3", "+", "-1é", "-é"] {
+        assert!(parse_due(input, now()).is_err(), "{}", input);
+    }
+    assert_eq!(parse_due("-3d", now()).unwrap(), Some(Due::Date(date(8))));
+}
+
+#[test]
+fn relative_labels() {
+    assert_eq!(Due::Date(date(13)).relative(now()), "in 2d");
+    assert_eq!(Due::Date(date(8)).relative(now()), "3d late");
+    assert!(Due::Date(date(11)).is_due_today(now()));
+    assert!(Due::DateTime(date(11).and_hms_opt(9, 0, 0).unwrap()).is_overdue(now()));
+}
+
+#[test]
+fn due_survives_line_format() {
+    let mut task = Task::from_description("pay rent").unwrap();
+    task.set_due(Some(Due::DateTime(date(30).and_hms_opt(9, 15, 0).unwrap())));
+    assert_eq!(decode(&encode(&task), 1).unwrap().due(), task.due());
+}
@@ -36,7 +36,7 @@ fn display_tasks() {
         .expect("error while adding a new task");
     assert_eq!(
         test.app.tasks_into_string(),
-        "Hello World [] Hello World \n"
+        "Hello World [ ] Hello World \n"
     );
 }
 
@@ -0,0 +1,41 @@
+use model::common::{Task, TaskError};
+use model::line::{decode, encode};
+use model::status::Status;
+use model::store::{LineStore, TaskStore};
+
+#[test]
+fn round_trips_special_characters() {
+    let mut task = Task::new(
+        3,
+        String::from("buy milk, eggs \"and\" bread"),
+        String::from("first line\nsecond\tline \\ done"),
+    )
+    .expect("error creating new task");
+    task.set_status(Status::Done);
+
+    let line = encode(&task);
+    assert!(!line.contains('\n'));
+
+    let decoded = decode(&line, 1).expect("error while decoding");
+    assert_eq!(decoded.get_id(), 3);
+    assert!(decoded.completed());
+    assert_eq!(decoded.description(), task.description());
+    assert_eq!(decoded.body(), task.body());
+}
+
+#[test]
+fn reports_offending_line() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("user_data");
+    std::fs::write(&path, "0\tfalse\tfine\t\n1\tmaybe\tbroken\t\n").unwrap();
+
+    let err = LineStore::new(path).unwrap().load().unwrap_err();
+    assert!(matches!(
+        err,
+        TaskError::Parse {
+            line: 2,
+            column: 3,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::setup_test_app;
+use model::common::{Task, TaskError};
+use todo_app::app::{App, SortMode};
+
+fn descriptions(app: &App) -> Vec<String> {
+    app.tasks.iter().map(Task::description).collect()
+}
+
+#[test]
+fn moves_persist_in_the_data_file() {
+    let mut test_app = setup_test_app();
+    let app = &mut test_app.app;
+    for description in ["a", "b", "c"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    let ids: Vec<usize> = app.tasks.iter().map(Task::get_id).collect();
+
+    app.move_task(ids[2], true).unwrap();
+    app.move_task(ids[2], true).unwrap();
+    // already at the top
+    app.move_task(ids[2], true).unwrap();
+    app.move_task(ids[0], false).unwrap();
+    assert_eq!(descriptions(app), ["c", "b", "a"]);
+    assert_eq!(app.selected_id(), Some(ids[0]));
+
+    let path = app.store.path().unwrap().to_path_buf();
+    let reopened = App::new(path).unwrap();
+    assert_eq!(descriptions(&reopened), ["c", "b", "a"]);
+}
+
+#[test]
+fn subtasks_move_among_their_siblings() {
+    let mut test_app = setup_test_app();
+    let app = &mut test_app.app;
+    for description in ["parent", "one", "two", "next"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    let ids: Vec<usize> = app.tasks.iter().map(Task::get_id).collect();
+    app.change_task_parent(ids[1], Some(ids[0])).unwrap();
+    app.change_task_parent(ids[2], Some(ids[0])).unwrap();
+
+    // no sibling above within the parent
+    app.move_task(ids[1], true).unwrap();
+    assert_eq!(descriptions(app), ["parent", "one", "two", "next"]);
+    app.move_task(ids[2], true).unwrap();
+    assert_eq!(descriptions(app), ["parent", "two", "one", "next"]);
+
+    // the parent steps over the whole of its neighbour, and takes its
+    // subtasks along in the view
+    app.move_task(ids[3], true).unwrap();
+    let rows: Vec<String> = app
+        .view()
+        .into_iter()
+        .map(|i| app.tasks[i].description())
+        .collect();
+    assert_eq!(rows, ["next", "parent", "two", "one"]);
+
+    app.sort = SortMode::Priority;
+    assert!(matches!(
+        app.move_task(ids[3], false),
+        Err(TaskError::Validation { field: "sort", .. })
+    ));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::Task;
+use model::line::{decode, encode};
+use model::priority::Priority;
+use todo_app::app::SortMode;
+
+#[test]
+fn raise_and_lower_stop_at_the_ends() {
+    assert_eq!(Priority::None.raise(), Priority::Low);
+    assert_eq!(Priority::Urgent.raise(), Priority::Urgent);
+    assert_eq!(Priority::High.lower(), Priority::Medium);
+    assert_eq!(Priority::None.lower(), Priority::None);
+}
+
+#[test]
+fn parses_names() {
+    assert_eq!("HIGH".parse::<Priority>().unwrap(), Priority::High);
+    assert_eq!("".parse::<Priority>().unwrap(), Priority::None);
+    assert!("soon".parse::<Priority>().is_err());
+}
+
+#[test]
+fn priority_survives_line_format() {
+    let mut task = Task::from_description("call back").unwrap();
+    task.set_priority(Priority::Medium);
+    assert_eq!(
+        decode(&encode(&task), 1).unwrap().priority(),
+        Priority::Medium
+    );
+}
+
+#[test]
+fn selection_follows_task_when_sorting() {
+    let mut app = setup_memory_app();
+    for description in ["first", "second", "third"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.sort = SortMode::Priority;
+    app.state.select(Some(2));
+
+    app.change_task_priority(2, Priority::High).unwrap();
+    assert_eq!(app.view(), [2, 0, 1]);
+    assert_eq!(app.state.selected(), Some(0));
+    assert_eq!(app.selected(), Some(2));
+}
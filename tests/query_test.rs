@@ -0,0 +1,126 @@
+mod common;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use common::setup_memory_app;
+use model::common::{Task, TaskError};
+use model::due::parse_due;
+use model::priority::Priority;
+use model::query::Query;
+use model::status::Status;
+use model::tags::parse_tokens;
+use todo_app::app::{App, CurrentScreen};
+
+fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 6, 2)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap()
+}
+
+fn tasks() -> Vec<Task> {
+    let mut tasks = Vec::new();
+    for (description, due, priority, status) in [
+        (
+            "send invoice +work #mail",
+            "2025-06-04",
+            Priority::High,
+            Status::Todo,
+        ),
+        (
+            "plan trip #later",
+            "2025-07-01",
+            Priority::Low,
+            Status::Todo,
+        ),
+        ("file taxes +home", "", Priority::Urgent, Status::InProgress),
+        (
+            "water plants +home",
+            "2025-06-01",
+            Priority::None,
+            Status::Done,
+        ),
+    ] {
+        let tokens = parse_tokens(description);
+        let mut task = Task::from_description(&tokens.description).unwrap();
+        task.set_tags(tokens.tags);
+        task.set_project(tokens.project);
+        task.set_due(parse_due(due, now()).unwrap());
+        task.set_priority(priority);
+        task.set_status(status);
+        tasks.push(task);
+    }
+    tasks[1].set_body(String::from("Quarterly report first"));
+    tasks
+}
+
+fn matching(query: &str) -> Vec<usize> {
+    let query = Query::parse(query, now()).unwrap();
+    tasks()
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| query.matches(task))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[test]
+fn terms_must_all_match() {
+    assert_eq!(matching(""), [0, 1, 2, 3]);
+    assert_eq!(matching("status:todo tag:mail"), [0]);
+    assert_eq!(matching("status:open,done -tag:later"), [0, 2, 3]);
+    assert_eq!(matching("status:closed"), [3]);
+    assert_eq!(matching("+home prio>=high"), [2]);
+    assert_eq!(matching("due<7d"), [0, 3]);
+    assert_eq!(matching("due>=2025-06-04"), [0, 1]);
+    assert_eq!(matching("due:none"), [2]);
+    assert_eq!(matching(r#""QUARTERLY REPORT""#), [1]);
+    assert_eq!(matching("plants"), [3]);
+}
+
+#[test]
+fn errors_point_at_the_term() {
+    let error = |input: &str| match Query::parse(input, now()) {
+        Err(TaskError::Query { column, .. }) => column,
+        other => panic!("expected a query error, got {:?}", other),
+    };
+    assert_eq!(error("tag:work colour:red"), 10);
+    assert_eq!(error("status:later"), 1);
+    assert_eq!(error("  prio>loud"), 3);
+    assert_eq!(error("tag<work"), 1);
+    assert_eq!(error("due:"), 1);
+    assert_eq!(error(r#"tag:a "open"#), 7);
+    assert_eq!(
+        Query::parse("prio>=", now()).unwrap_err().to_string(),
+        "invalid filter at column 1: `prio` needs a value"
+    );
+}
+
+fn setup_app() -> App {
+    let mut app = setup_memory_app();
+    for task in tasks() {
+        app.add_task(task).unwrap();
+    }
+    app
+}
+
+#[test]
+fn prompt_narrows_the_view() {
+    let mut app = setup_app();
+    assert_eq!(app.view(), [0, 1, 2]);
+
+    app.set_query("+home").unwrap();
+    assert_eq!(app.view(), [2]);
+    // asking for closed tasks lists them even while they are hidden
+    app.set_query("status:done").unwrap();
+    assert_eq!(app.view(), [3]);
+
+    assert!(app.set_query("due<soonish").is_err());
+    assert_eq!(
+        app.filter.query.as_ref().unwrap().to_string(),
+        "status:done"
+    );
+
+    app.set_query("  ").unwrap();
+    assert!(app.filter.is_empty());
+    assert_eq!(app.current_screen, CurrentScreen::Main);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use common::setup_memory_app;
+use model::common::Task;
+use model::due::Due;
+use model::line::{decode, encode};
+use model::recur::Recurrence;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+// a Wednesday
+fn now() -> NaiveDateTime {
+    date(2025, 6, 11).and_hms_opt(18, 0, 0).unwrap()
+}
+
+fn next(rule: &str, due: NaiveDate) -> Due {
+    let rule: Recurrence = rule.parse().unwrap();
+    rule.next_due(Some(Due::Date(due)), now()).unwrap()
+}
+
+#[test]
+fn rules_round_trip() {
+    for rule in [
+        "daily",
+        "weekly",
+        "weekly mon,thu",
+        "monthly",
+        "monthly 31",
+        "after 3d",
+    ] {
+        assert_eq!(rule.parse::<Recurrence>().unwrap().to_string(), rule);
+    }
+    assert_eq!(
+        "Weekly Thursday monday".parse::<Recurrence>().unwrap(),
+        "weekly mon,thu".parse().unwrap()
+    );
+    assert!("hourly".parse::<Recurrence>().is_err());
+    assert!("monthly 32".parse::<Recurrence>().is_err());
+    assert_eq!(
+        "after 3".parse::<Recurrence>().unwrap(),
+        "after 3d".parse().unwrap()
+    );
+    assert!("after 3ddd".parse::<Recurrence>().is_err());
+}
+
+#[test]
+fn next_due_dates() {
+    assert_eq!(
+        next("daily", date(2025, 6, 11)),
+        Due::Date(date(2025, 6, 12))
+    );
+    // missed occurrences are skipped
+    assert_eq!(
+        next("daily", date(2025, 6, 1)),
+        Due::Date(date(2025, 6, 12))
+    );
+    assert_eq!(
+        next("weekly mon,thu", date(2025, 6, 9)),
+        Due::Date(date(2025, 6, 12))
+    );
+    assert_eq!(
+        next("weekly", date(2025, 6, 9)),
+        Due::Date(date(2025, 6, 16))
+    );
+    assert_eq!(
+        next("monthly 31", date(2025, 5, 31)),
+        Due::Date(date(2025, 6, 30))
+    );
+    assert_eq!(
+        next("after 3d", date(2025, 6, 1)),
+        Due::Date(date(2025, 6, 14))
+    );
+}
+
+#[test]
+fn completing_spawns_the_next_occurrence() {
+    let mut app = setup_memory_app();
+    let mut task = Task::from_description("weekly review").unwrap();
+    task.set_recurrence(Some("daily".parse().unwrap()));
+    app.add_task(task).unwrap();
+
+    app.set_task_done(0, true).unwrap();
+    assert_eq!(app.tasks.len(), 2);
+    assert!(app.tasks[0].completed());
+    assert!(app.tasks[0].recurrence().is_none());
+    assert!(!app.tasks[1].completed());
+    assert_eq!(app.tasks[1].description(), "weekly review");
+    assert!(app.tasks[1].due().is_some());
+
+    // reopening and closing the old one does not spawn again
+    app.set_task_done(0, false).unwrap();
+    app.set_task_done(0, true).unwrap();
+    assert_eq!(app.tasks.len(), 2);
+}
+
+#[test]
+fn recurrence_survives_line_format() {
+    let mut task = Task::from_description("stand-up notes").unwrap();
+    task.set_recurrence(Some("weekly mon,tue".parse().unwrap()));
+    assert_eq!(
+        decode(&encode(&task), 1).unwrap().recurrence(),
+        task.recurrence()
+    );
+}
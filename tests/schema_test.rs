@@ -0,0 +1,70 @@
+use model::common::TaskError;
+use model::schema::{self, SCHEMA_VERSION};
+use model::store::{JsonStore, TaskStore};
+use serde_json::{json, Value};
+use todo_app::app::App;
+
+#[test]
+fn upgrades_bare_array() {
+    let legacy = json!([{ "id": 0, "description": "old", "completed": false, "body": "" }]);
+    let document = schema::parse(legacy).expect("error while migrating");
+    assert_eq!(document.schema_version, SCHEMA_VERSION);
+    assert_eq!(document.tasks[0].description(), "old");
+}
+
+#[test]
+fn counter_starts_after_highest_id() {
+    let legacy = json!({
+        "schema_version": 8,
+        "tasks": [
+            { "id": 4, "description": "a", "completed": false, "body": "" },
+            { "id": 1, "description": "b", "completed": false, "body": "" }
+        ]
+    });
+    assert_eq!(schema::parse(legacy).unwrap().next_id, 5);
+
+    let ahead = json!({ "schema_version": SCHEMA_VERSION, "next_id": 9, "tasks": [] });
+    assert_eq!(schema::parse(ahead).unwrap().next_id, 9);
+}
+
+#[test]
+fn rejects_newer_schema() {
+    let newer = json!({ "schema_version": SCHEMA_VERSION + 1, "tasks": [] });
+    assert!(matches!(
+        schema::parse(newer),
+        Err(TaskError::SchemaVersion { .. })
+    ));
+}
+
+#[test]
+fn json_store_writes_envelope() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+    std::fs::write(
+        &path,
+        r#"[{ "id": 0, "description": "old", "completed": true, "body": "" }]"#,
+    )
+    .unwrap();
+
+    let mut store = JsonStore::new(path.clone()).unwrap();
+    let tasks = store.load().unwrap();
+    store.save(&tasks).unwrap();
+
+    let written: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(written["schema_version"], json!(SCHEMA_VERSION));
+    assert_eq!(written["tasks"][0]["description"], json!("old"));
+}
+
+#[test]
+fn newer_file_is_not_replaced_by_backup() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+    std::fs::write(model::store::backup_path(&path), "[]").unwrap();
+    std::fs::write(
+        &path,
+        json!({ "schema_version": SCHEMA_VERSION + 1, "tasks": [] }).to_string(),
+    )
+    .unwrap();
+
+    assert!(App::with_json(path).is_err());
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::Task;
+use model::search::{fuzzy_match, SearchHit};
+use todo_app::app::{App, CurrentScreen};
+
+fn setup_app() -> App {
+    let mut app = setup_memory_app();
+    for (description, body) in [
+        ("buy milk", ""),
+        ("write report", "numbers from the bank"),
+        ("book flights", ""),
+        ("call the bank", ""),
+    ] {
+        let mut task = Task::from_description(description).unwrap();
+        task.set_body(String::from(body));
+        app.add_task(task).unwrap();
+    }
+    app.state.select(Some(0));
+    app
+}
+
+#[test]
+fn prefers_consecutive_characters() {
+    assert_eq!(
+        fuzzy_match("RepOrt", "write report"),
+        Some(vec![6, 7, 8, 9, 10, 11])
+    );
+    assert_eq!(fuzzy_match("wrt", "write report"), Some(vec![0, 1, 3]));
+    assert_eq!(fuzzy_match("b k", "book"), Some(vec![0, 3]));
+    assert_eq!(fuzzy_match("xyz", "write report"), None);
+    assert_eq!(fuzzy_match("", "anything"), None);
+}
+
+#[test]
+fn typing_jumps_to_the_first_match() {
+    let mut app = setup_app();
+    app.start_search();
+    assert_eq!(app.current_screen, CurrentScreen::Search);
+    app.set_search(String::from("boo"));
+    assert_eq!(app.selected(), Some(2));
+
+    app.set_search(String::from("bank"));
+    assert_eq!(app.search_rows(), [1, 3]);
+    assert_eq!(app.selected(), Some(1));
+    assert_eq!(app.search_hit(1), Some(SearchHit::Body));
+    assert_eq!(
+        app.search_hit(3),
+        Some(SearchHit::Description(vec![9, 10, 11, 12]))
+    );
+
+    app.set_search(String::from("nothing like it"));
+    assert_eq!(app.selected(), Some(0));
+}
+
+#[test]
+fn n_and_shift_n_wrap_around() {
+    let mut app = setup_app();
+    app.start_search();
+    app.set_search(String::from("bank"));
+    app.jump_to_match(true);
+    assert_eq!(app.selected(), Some(3));
+    app.jump_to_match(true);
+    assert_eq!(app.selected(), Some(1));
+    app.jump_to_match(false);
+    assert_eq!(app.selected(), Some(3));
+}
+
+#[test]
+fn clearing_keeps_the_selected_task() {
+    let mut app = setup_app();
+    app.show_done = false;
+    app.start_search();
+    app.set_search(String::from("flights"));
+    app.set_task_done(0, true).unwrap();
+    assert_eq!(
+        app.tasks[app.selected().unwrap()].description(),
+        "book flights"
+    );
+
+    app.clear_search();
+    assert!(app.search.is_empty());
+    assert_eq!(
+        app.tasks[app.selected().unwrap()].description(),
+        "book flights"
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::setup_memory_app;
+use model::common::Task;
+use model::due::Due;
+use model::priority::Priority;
+use model::status::Status;
+use todo_app::app::{App, SortMode};
+
+fn setup_app() -> App {
+    let mut app = setup_memory_app();
+    for (description, due, status) in [
+        ("pay rent", Some(3), Status::Waiting),
+        ("Book dentist", None, Status::Todo),
+        ("answer mail", Some(1), Status::InProgress),
+    ] {
+        let mut task = Task::from_description(description).unwrap();
+        task.set_due(due.map(|day| Due::Date(NaiveDate::from_ymd_opt(2025, 6, day).unwrap())));
+        task.set_status(status);
+        app.add_task(task).unwrap();
+    }
+    app
+}
+
+#[test]
+fn modes_and_directions() {
+    let mut app = setup_app();
+    for (mode, expected, reversed) in [
+        (SortMode::Manual, [0, 1, 2], [2, 1, 0]),
+        (SortMode::Due, [2, 0, 1], [0, 2, 1]),
+        (SortMode::Alphabetical, [2, 1, 0], [0, 1, 2]),
+        (SortMode::Status, [1, 2, 0], [0, 2, 1]),
+    ] {
+        app.sort = mode;
+        app.sort_reversed = false;
+        assert_eq!(app.view(), expected, "{:?}", mode);
+        app.sort_reversed = true;
+        assert_eq!(app.view(), reversed, "{:?} reversed", mode);
+    }
+
+    app.change_task_priority(1, Priority::High).unwrap();
+    app.sort = SortMode::Priority;
+    app.sort_reversed = true;
+    // equal priorities still come by due date
+    assert_eq!(app.view(), [2, 0, 1]);
+}
+
+#[test]
+fn cycling_keeps_the_selected_task() {
+    let mut app = setup_app();
+    app.state.select(Some(0));
+    let mut seen = Vec::new();
+    loop {
+        app.cycle_sort();
+        assert_eq!(app.selected_id(), Some(0));
+        seen.push(app.sort);
+        if app.sort == SortMode::Manual {
+            break;
+        }
+    }
+    assert_eq!(seen.len(), 8);
+
+    app.reverse_sort();
+    assert!(app.sort_reversed);
+    assert_eq!(app.selected_id(), Some(0));
+    assert_eq!(app.state.selected(), Some(2));
+    app.cycle_sort();
+    assert!(!app.sort_reversed);
+}
@@ -0,0 +1,85 @@
+use model::common::{Task, TaskError};
+use model::store::{JsonStore, SqliteStore, TaskStore};
+use todo_app::app::App;
+
+#[test]
+fn update_only_touches_changed_task() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.db");
+
+    let mut app = App::with_sqlite(path.clone()).expect("failed to instantiate app");
+    for description in ["first", "second", "third"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .expect("error while adding a new task");
+    }
+    app.set_task_done(1, true)
+        .expect("error while updating task");
+    app.remove_task(0).expect("error while removing task");
+
+    let tasks = SqliteStore::new(path).unwrap().load().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].description(), "second");
+    assert!(tasks[0].completed());
+    assert_eq!(tasks[1].description(), "third");
+}
+
+#[test]
+fn imports_json_on_first_launch() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let json_path = temp_dir.path().join("data.json");
+    JsonStore::new(json_path)
+        .unwrap()
+        .save(&[Task::new(0, String::from("from json"), String::new()).unwrap()])
+        .unwrap();
+
+    let app = App::with_sqlite(temp_dir.path().join("data.db")).expect("failed to instantiate app");
+    assert_eq!(app.tasks.len(), 1);
+    assert_eq!(app.tasks[0].description(), "from json");
+}
+
+#[test]
+fn commits_from_another_connection_are_detected() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.db");
+    let mut ours = SqliteStore::new(path.clone()).unwrap();
+    let mut theirs = SqliteStore::new(path).unwrap();
+    ours.load().unwrap();
+    assert!(!ours.changed_on_disk().unwrap());
+
+    // our own writes don't count
+    ours.append(&Task::new(0, String::from("mine"), String::new()).unwrap())
+        .unwrap();
+    assert!(!ours.changed_on_disk().unwrap());
+
+    theirs
+        .append(&Task::new(1, String::from("theirs"), String::new()).unwrap())
+        .unwrap();
+    assert!(ours.changed_on_disk().unwrap());
+    assert!(matches!(ours.save(&[]), Err(TaskError::Conflict { .. })));
+
+    assert_eq!(ours.load().unwrap().len(), 2);
+    assert!(!ours.changed_on_disk().unwrap());
+}
+
+#[test]
+fn moving_a_task_only_updates_positions() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.db");
+
+    let mut app = App::with_sqlite(path.clone()).expect("failed to instantiate app");
+    for description in ["first", "second", "third"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .expect("error while adding a new task");
+    }
+    // added behind the app's back, so a full rewrite would drop it
+    SqliteStore::new(path.clone())
+        .unwrap()
+        .append(&Task::new(3, String::from("theirs"), String::new()).unwrap())
+        .unwrap();
+
+    app.move_task(2, true).expect("error while moving task");
+
+    let tasks = SqliteStore::new(path).unwrap().load().unwrap();
+    let descriptions: Vec<String> = tasks.iter().map(|task| task.description()).collect();
+    assert_eq!(descriptions, ["first", "third", "second", "theirs"]);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::{Stamp, Task};
+use model::line::{decode, encode};
+use model::status::Status;
+use todo_app::app::{Filter, SortMode};
+
+#[test]
+fn setters_keep_timestamps() {
+    let mut app = setup_memory_app();
+    app.add_task(Task::from_description("write").unwrap())
+        .unwrap();
+    let created = app.tasks[0].stamp(Stamp::Created).unwrap();
+    assert_eq!(app.tasks[0].stamp(Stamp::Updated), Some(created));
+    assert_eq!(app.tasks[0].stamp(Stamp::Completed), None);
+
+    app.change_task_body(0, String::from("more")).unwrap();
+    assert!(app.tasks[0].stamp(Stamp::Updated).unwrap() >= created);
+
+    app.set_task_done(0, true).unwrap();
+    assert!(app.tasks[0].stamp(Stamp::Completed).is_some());
+    app.set_task_done(0, false).unwrap();
+    assert_eq!(app.tasks[0].stamp(Stamp::Completed), None);
+    assert_eq!(app.tasks[0].stamp(Stamp::Created), Some(created));
+}
+
+#[test]
+fn timestamps_survive_line_format() {
+    let mut task = Task::from_description("stamped").unwrap();
+    task.set_status(Status::Done);
+    let decoded = decode(&encode(&task), 1).unwrap();
+    for stamp in Stamp::ALL {
+        assert_eq!(decoded.stamp(stamp), task.stamp(stamp));
+    }
+
+    // records from before the timestamps existed have none
+    let old = decode("0\tfalse\told\tbody", 1).unwrap();
+    assert!(Stamp::ALL.iter().all(|&stamp| old.stamp(stamp).is_none()));
+}
+
+#[test]
+fn sort_and_filter_by_completion() {
+    let mut app = setup_memory_app();
+    for description in ["a", "b", "c"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.show_done = true;
+    app.set_task_done(2, true).unwrap();
+    app.set_task_done(0, true).unwrap();
+
+    app.sort = SortMode::Completed;
+    assert_eq!(app.view(), [0, 2, 1]);
+
+    app.set_filter(Filter {
+        recent: Some((Stamp::Completed, 1)),
+        ..Default::default()
+    });
+    assert_eq!(app.view(), [0, 2]);
+}
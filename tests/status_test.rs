@@ -0,0 +1,89 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::{Task, TaskError};
+use model::config::Config;
+use model::line::{decode, encode};
+use model::schema;
+use model::status::Status;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+#[test]
+fn parses_names() {
+    assert_eq!("In Progress".parse::<Status>().unwrap(), Status::InProgress);
+    assert_eq!("cancelled".parse::<Status>().unwrap(), Status::Cancelled);
+    assert!("later".parse::<Status>().is_err());
+}
+
+#[test]
+fn tab_follows_the_configured_cycle() {
+    let mut app = setup_memory_app();
+    app.add_task(Task::from_description("write").unwrap())
+        .unwrap();
+    for expected in [Status::InProgress, Status::Done, Status::Todo] {
+        app.cycle_task_status(0).unwrap();
+        assert_eq!(app.tasks[0].status(), expected);
+    }
+
+    app.config.status_cycle = vec![Status::Todo, Status::Waiting];
+    app.change_task_status(0, Status::Cancelled).unwrap();
+    app.cycle_task_status(0).unwrap();
+    assert_eq!(app.tasks[0].status(), Status::Todo);
+    app.cycle_task_status(0).unwrap();
+    assert_eq!(app.tasks[0].status(), Status::Waiting);
+}
+
+#[test]
+fn cancelled_tasks_are_closed() {
+    let mut app = setup_memory_app();
+    for description in ["ask", "wait"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.change_task_blockers(1, BTreeSet::from([0])).unwrap();
+    assert!(matches!(
+        app.change_task_status(1, Status::Done),
+        Err(TaskError::Blocked { .. })
+    ));
+
+    app.change_task_status(0, Status::Cancelled).unwrap();
+    assert!(app.tasks[0].completed());
+    assert_eq!(app.view(), [1]);
+    app.change_task_status(1, Status::Done).unwrap();
+}
+
+#[test]
+fn loads_completed_flag_from_older_files() {
+    let legacy = json!({
+        "schema_version": 9,
+        "next_id": 2,
+        "tasks": [
+            { "id": 0, "description": "a", "completed": true, "body": "" },
+            { "id": 1, "description": "b", "completed": false, "body": "" }
+        ]
+    });
+    let tasks = schema::parse(legacy).unwrap().tasks;
+    assert_eq!(tasks[0].status(), Status::Done);
+    assert_eq!(tasks[1].status(), Status::Todo);
+
+    assert_eq!(decode("0\ttrue\told\t", 1).unwrap().status(), Status::Done);
+    let mut task = Task::from_description("paused").unwrap();
+    task.set_status(Status::Waiting);
+    assert_eq!(decode(&encode(&task), 1).unwrap().status(), Status::Waiting);
+}
+
+#[test]
+fn config_defaults_and_validation() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("config.json");
+    assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+    std::fs::write(&path, r#"{ "status_cycle": ["todo", "waiting", "done"] }"#).unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.next_status(Status::Waiting), Status::Done);
+    assert_eq!(config.next_status(Status::InProgress), Status::Todo);
+
+    std::fs::write(&path, r#"{ "status_cycle": [] }"#).unwrap();
+    assert!(Config::load(&path).is_err());
+}
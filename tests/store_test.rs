@@ -0,0 +1,193 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::{Task, TaskError};
+use model::store::{JsonStore, MemoryStore, TaskStore};
+use std::path::PathBuf;
+use todo_app::app::App;
+
+#[test]
+fn memory_store_keeps_tasks() {
+    let mut app = setup_memory_app();
+    app.add_task(Task::from_description("Hello World").unwrap())
+        .expect("error while adding a new task");
+    assert_eq!(app.store.load().unwrap().len(), 1);
+}
+
+#[test]
+fn ids_are_not_reused_after_removal() {
+    let tasks = vec![
+        Task::new(0, String::from("first"), String::new()).unwrap(),
+        Task::new(1, String::from("second"), String::new()).unwrap(),
+    ];
+    let mut app = App::with_store(Box::new(MemoryStore::new(tasks))).unwrap();
+    app.remove_task(0).expect("error while removing task");
+    app.add_task(Task::from_description("third").unwrap())
+        .expect("error while adding a new task");
+    assert_eq!(app.tasks[1].get_id(), 2);
+}
+
+#[test]
+fn highest_id_stays_retired_after_reopen() {
+    let open: [fn(PathBuf) -> Result<App, TaskError>; 3] =
+        [App::new, App::with_json, App::with_sqlite];
+    for (open, name) in open.into_iter().zip(["data", "data.json", "data.db"]) {
+        // separate directories, or the database would import the JSON file
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = temp_dir.path().join(name);
+        let mut app = open(path.clone()).expect("failed to instantiate app");
+        for description in ["first", "second"] {
+            app.add_task(Task::from_description(description).unwrap())
+                .expect("error while adding a new task");
+        }
+        app.remove_task(1).expect("error while removing task");
+
+        let mut app = open(path).expect("failed to instantiate app");
+        app.add_task(Task::from_description("third").unwrap())
+            .expect("error while adding a new task");
+        assert_eq!(app.tasks[1].get_id(), 2, "{}", name);
+    }
+}
+
+#[test]
+fn json_store_round_trip() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+
+    let mut app = App::with_json(path.clone()).expect("failed to instantiate app");
+    app.add_task(Task::from_description("Hello World").unwrap())
+        .expect("error while adding a new task");
+
+    let tasks = JsonStore::new(path).unwrap().load().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].description(), "Hello World");
+}
+
+#[test]
+fn save_keeps_the_previous_file_as_backup() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+
+    let mut app = App::with_json(path.clone()).expect("failed to instantiate app");
+    for description in ["first", "second"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .expect("error while adding a new task");
+    }
+    let backup = JsonStore::new(model::store::backup_path(&path))
+        .unwrap()
+        .load()
+        .unwrap();
+    assert_eq!(backup.len(), 1);
+    assert_eq!(JsonStore::new(path).unwrap().load().unwrap().len(), 2);
+    // no temporary file is left behind
+    assert!(std::fs::read_dir(temp_dir.path())
+        .unwrap()
+        .all(|entry| !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")));
+}
+
+#[test]
+fn json_store_falls_back_to_backup() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+
+    let mut app = App::with_json(path.clone()).expect("failed to instantiate app");
+    app.add_task(Task::from_description("first").unwrap())
+        .expect("error while adding a new task");
+    app.add_task(Task::from_description("second").unwrap())
+        .expect("error while adding a new task");
+    std::fs::write(&path, "[{\"id\": 0, \"desc").unwrap();
+
+    let app = App::with_json(path).expect("failed to instantiate app");
+    assert_eq!(app.tasks.len(), 1);
+    assert_eq!(app.tasks[0].description(), "first");
+}
+
+#[test]
+fn concurrent_change_prompts_instead_of_overwriting() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+
+    let mut first = App::with_json(path.clone()).expect("failed to instantiate app");
+    let mut second = App::with_json(path.clone()).expect("failed to instantiate app");
+    second
+        .add_task(Task::from_description("from second").unwrap())
+        .expect("error while adding a new task");
+
+    let result = first.add_task(Task::from_description("from first").unwrap());
+    assert!(matches!(result, Err(TaskError::Conflict { .. })));
+    assert_eq!(first.tasks.len(), 1);
+    assert_eq!(
+        JsonStore::new(path.clone()).unwrap().load().unwrap().len(),
+        1
+    );
+
+    first.reload().expect("error while reloading");
+    assert_eq!(first.tasks.len(), 1);
+    assert_eq!(first.tasks[0].description(), "from second");
+}
+
+#[test]
+fn keeping_mine_overwrites_but_keeps_ids_retired() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+
+    let mut first = App::with_json(path.clone()).expect("failed to instantiate app");
+    let mut second = App::with_json(path.clone()).expect("failed to instantiate app");
+    for description in ["kept", "removed"] {
+        second
+            .add_task(Task::from_description(description).unwrap())
+            .expect("error while adding a new task");
+    }
+    second.remove_task(1).expect("error while removing a task");
+
+    let result = first.add_task(Task::from_description("mine").unwrap());
+    assert!(matches!(result, Err(TaskError::Conflict { .. })));
+    first.overwrite().expect("error while overwriting");
+
+    let on_disk = JsonStore::new(path.clone()).unwrap().load().unwrap();
+    assert_eq!(on_disk.len(), 1);
+    assert_eq!(on_disk[0].description(), "mine");
+    // saving goes on normally, past the ids the other process handed out
+    first
+        .add_task(Task::from_description("next").unwrap())
+        .expect("error while adding a new task");
+    assert_eq!(first.tasks[1].get_id(), 2);
+    assert_eq!(JsonStore::new(path).unwrap().load().unwrap().len(), 2);
+}
+
+#[test]
+fn reload_keeps_selected_task() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+
+    let mut first = App::with_json(path.clone()).expect("failed to instantiate app");
+    for description in ["first", "second", "third"] {
+        first
+            .add_task(Task::from_description(description).unwrap())
+            .expect("error while adding a new task");
+    }
+    first.state.select(Some(2));
+
+    let mut second = App::with_json(path).expect("failed to instantiate app");
+    second.remove_task(0).expect("error while removing task");
+
+    first.reload().expect("error while reloading");
+    assert_eq!(first.state.selected(), Some(1));
+    assert_eq!(first.tasks[1].description(), "third");
+}
+
+#[test]
+fn unreadable_file_reports_path() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("data.json");
+    std::fs::create_dir(&path).unwrap();
+
+    match App::with_json(path.clone()) {
+        Err(TaskError::Io { path: err_path, .. }) => assert_eq!(err_path, path),
+        other => panic!("expected an I/O error, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::{Task, TaskError};
+use model::line::{decode, encode};
+use std::collections::BTreeSet;
+use todo_app::app::App;
+
+/// `plan` with steps `book` and `pack`, then `other`.
+fn setup_tree() -> App {
+    let mut app = setup_memory_app();
+    for description in ["plan", "other", "book", "pack"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.change_task_parent(2, Some(0)).unwrap();
+    app.change_task_parent(3, Some(0)).unwrap();
+    app
+}
+
+#[test]
+fn subtasks_follow_their_parent() {
+    let mut app = setup_tree();
+    assert_eq!(app.tree(), [(0, 0), (2, 1), (3, 1), (1, 0)]);
+
+    app.state.select(Some(0));
+    app.set_collapsed(true);
+    assert_eq!(app.view(), [0, 1]);
+    app.set_collapsed(false);
+    assert_eq!(app.view().len(), 4);
+}
+
+#[test]
+fn indent_and_outdent() {
+    let mut app = setup_tree();
+    // `pack` goes under `book`, its sibling above
+    app.state.select(Some(2));
+    app.indent().unwrap();
+    assert_eq!(app.tasks[3].parent(), Some(2));
+    assert_eq!(app.tree()[2], (3, 2));
+
+    app.outdent().unwrap();
+    app.outdent().unwrap();
+    assert_eq!(app.tasks[3].parent(), None);
+}
+
+#[test]
+fn parent_cycles_are_rejected() {
+    let mut app = setup_tree();
+    let err = app.change_task_parent(0, Some(2)).unwrap_err();
+    assert!(matches!(
+        err,
+        TaskError::Validation {
+            field: "parent",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn progress_and_cascade() {
+    let mut app = setup_tree();
+    app.show_done = true;
+    app.set_task_done(2, true).unwrap();
+    assert_eq!(app.progress(0), Some((1, 2)));
+    assert_eq!(app.progress(1), None);
+
+    app.cascade = true;
+    app.set_task_done(0, true).unwrap();
+    assert!(app
+        .tasks
+        .iter()
+        .filter(|t| t.get_id() != 1)
+        .all(Task::completed));
+}
+
+#[test]
+fn cascade_is_refused_while_a_subtask_is_blocked() {
+    let mut app = setup_tree();
+    app.cascade = true;
+    // waiting on a sibling inside the cascade is fine, on `other` it is not
+    app.change_task_blockers(3, BTreeSet::from([2, 1])).unwrap();
+    assert!(matches!(
+        app.set_task_done(0, true),
+        Err(TaskError::Blocked { id: 3, ref by }) if by == &[1]
+    ));
+    assert!(app.tasks.iter().all(|task| !task.completed()));
+
+    app.set_task_done(1, true).unwrap();
+    app.set_task_done(0, true).unwrap();
+    assert!(app.tasks.iter().all(Task::completed));
+}
+
+#[test]
+fn removing_a_parent_keeps_its_subtasks() {
+    let mut app = setup_tree();
+    app.remove_task(0).unwrap();
+    assert!(app.tasks.iter().all(|task| task.parent().is_none()));
+}
+
+#[test]
+fn parent_survives_line_format() {
+    let mut task = Task::from_description("step").unwrap();
+    task.set_parent(Some(7));
+    assert_eq!(decode(&encode(&task), 1).unwrap().parent(), Some(7));
+}
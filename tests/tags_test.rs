@@ -0,0 +1,65 @@
+mod common;
+
+use common::setup_memory_app;
+use model::common::Task;
+use model::line::{decode, encode};
+use model::tags::{parse_tokens, with_tokens};
+use todo_app::app::{Filter, PickerEntry};
+
+#[test]
+fn tokens_are_pulled_out_of_the_description() {
+    let tokens = parse_tokens("call  mom +family @phone #weekly about C# 5+3");
+    assert_eq!(tokens.description, "call mom about C# 5+3");
+    assert_eq!(tokens.project.as_deref(), Some("family"));
+    assert_eq!(
+        tokens.tags.into_iter().collect::<Vec<_>>(),
+        ["phone", "weekly"]
+    );
+
+    // text without tokens is kept exactly
+    assert_eq!(parse_tokens("  spaced  out ").description, "  spaced  out ");
+}
+
+#[test]
+fn tags_survive_line_format_and_editor() {
+    let mut task = Task::from_description("plan trip").unwrap();
+    let tokens = parse_tokens("plan trip +travel #summer #family");
+    task.set_tags(tokens.tags);
+    task.set_project(tokens.project);
+
+    let decoded = decode(&encode(&task), 1).unwrap();
+    assert_eq!(decoded.tags(), task.tags());
+    assert_eq!(decoded.project(), Some("travel"));
+    assert_eq!(with_tokens(&task), "plan trip +travel #family #summer");
+}
+
+#[test]
+fn filter_narrows_the_view() {
+    let mut app = setup_memory_app();
+    for description in ["a", "b", "c"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.change_task_text(0, "a +work #mail").unwrap();
+    app.change_task_text(1, "b +home #mail").unwrap();
+    app.change_task_text(2, "c +work #call").unwrap();
+    assert_eq!(
+        app.picker_entries()[..4],
+        [
+            PickerEntry::Project(String::from("home")),
+            PickerEntry::Project(String::from("work")),
+            PickerEntry::Tag(String::from("call")),
+            PickerEntry::Tag(String::from("mail")),
+        ]
+    );
+
+    let mut filter = Filter::default();
+    filter.toggle(&PickerEntry::Tag(String::from("mail")));
+    app.set_filter(filter.clone());
+    assert_eq!(app.view(), [0, 1]);
+
+    filter.toggle(&PickerEntry::Project(String::from("work")));
+    app.set_filter(filter);
+    assert_eq!(app.view(), [0]);
+    assert_eq!(app.selected(), Some(0));
+}
@@ -1,11 +1,12 @@
-use model::common::Task;
+use model::common::{Task, TaskError};
+use model::status::Status;
 
 #[test]
 fn set_is_done() {
     let mut task = Task::new(0, String::from("Hello World"), String::from("hello world"))
         .expect("error creating new task");
-    task.set_completed();
-    assert_eq!(task.completed(), true);
+    task.set_status(Status::Done);
+    assert!(task.completed());
 }
 
 #[test]
@@ -15,3 +16,15 @@ fn change_valid_text() {
     task.set_description(String::from("New Text"));
     assert_eq!(task.description(), String::from("New Text"));
 }
+
+#[test]
+fn empty_description_is_rejected() {
+    let err = Task::new(0, String::new(), String::from("hello world")).unwrap_err();
+    assert!(matches!(
+        err,
+        TaskError::Validation {
+            field: "description",
+            ..
+        }
+    ));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use common::setup_memory_app;
+use model::common::Task;
+use model::line::{decode, encode};
+use model::status::Status;
+use model::time::{format_duration, report, ReportBy, TimeEntry};
+use std::collections::BTreeSet;
+
+fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(2025, 6, day, hour, minute, 0)
+        .unwrap()
+}
+
+fn entry(start: DateTime<Local>, end: DateTime<Local>) -> TimeEntry {
+    TimeEntry {
+        start,
+        end: Some(end),
+    }
+}
+
+#[test]
+fn one_timer_runs_at_a_time() {
+    let mut app = setup_memory_app();
+    for description in ["write", "review"] {
+        app.add_task(Task::from_description(description).unwrap())
+            .unwrap();
+    }
+    app.toggle_timer(0).unwrap();
+    assert!(app.tasks[0].is_tracking());
+
+    app.toggle_timer(1).unwrap();
+    assert!(!app.tasks[0].is_tracking());
+    assert!(app.tasks[1].is_tracking());
+    assert_eq!(app.tasks[0].time_entries().len(), 1);
+
+    app.change_task_status(1, Status::Done).unwrap();
+    assert!(!app.tasks[1].is_tracking());
+}
+
+#[test]
+fn report_adds_up_per_task_tag_and_day() {
+    let mut first = Task::new(0, String::from("write"), String::new()).unwrap();
+    first.set_tags(BTreeSet::from([String::from("docs"), String::from("work")]));
+    first.set_time_entries(vec![
+        entry(at(1, 9, 0), at(1, 10, 30)),
+        // runs past midnight into the next day
+        entry(at(1, 23, 0), at(2, 1, 0)),
+    ]);
+    let mut second = Task::new(1, String::from("call"), String::new()).unwrap();
+    second.set_time_entries(vec![entry(at(2, 14, 0), at(2, 14, 20))]);
+    let tasks = [first, second];
+    let now = at(3, 0, 0);
+
+    let hours = |h: i64, m: i64| Duration::hours(h) + Duration::minutes(m);
+    assert_eq!(
+        report(&tasks, ReportBy::Task, None, None, now),
+        [
+            (String::from("0 write"), hours(3, 30)),
+            (String::from("1 call"), hours(0, 20)),
+        ]
+    );
+    assert_eq!(
+        report(&tasks, ReportBy::Tag, None, None, now),
+        [
+            (String::from("#docs"), hours(3, 30)),
+            (String::from("#work"), hours(3, 30)),
+            (String::from("untagged"), hours(0, 20)),
+        ]
+    );
+    let june_2 = NaiveDate::from_ymd_opt(2025, 6, 2);
+    assert_eq!(
+        report(&tasks, ReportBy::Day, june_2, None, now),
+        [(String::from("2025-06-02"), hours(1, 20))]
+    );
+    assert_eq!(format_duration(hours(26, 5)), "26:05:00");
+}
+
+#[test]
+fn time_entries_survive_line_format() {
+    let mut task = Task::from_description("tracked").unwrap();
+    task.set_time_entries(vec![entry(at(1, 9, 0), at(1, 10, 0))]);
+    task.start_timer(at(1, 11, 0));
+
+    let decoded = decode(&encode(&task), 1).unwrap();
+    assert_eq!(decoded.time_entries(), task.time_entries());
+    assert!(decoded.is_tracking());
+}
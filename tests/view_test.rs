@@ -0,0 +1,104 @@
+mod common;
+
+use chrono::{Days, Local};
+use common::setup_memory_app;
+use model::common::{Task, TaskError};
+use model::config::{Config, SavedView};
+use model::due::Due;
+use model::tags::parse_tokens;
+use todo_app::app::{App, View};
+
+fn setup_app() -> App {
+    let mut app = setup_memory_app();
+    let today = Local::now().date_naive();
+    for (description, due) in [
+        ("read mail", Some(today)),
+        ("fix bug +work", today.checked_sub_days(Days::new(2))),
+        ("plan sprint +work", None),
+        ("old +home", None),
+    ] {
+        let tokens = parse_tokens(description);
+        let mut task = Task::from_description(&tokens.description).unwrap();
+        task.set_project(tokens.project);
+        task.set_due(due.map(Due::Date));
+        app.add_task(task).unwrap();
+    }
+    app.set_task_done(3, true).unwrap();
+    app
+}
+
+#[test]
+fn sidebar_lists_builtin_and_project_views() {
+    let app = setup_app();
+    let views = app.views();
+    assert_eq!(
+        views.iter().map(View::label).collect::<Vec<_>>(),
+        ["All", "Inbox", "Today", "Overdue", "+home", "+work"]
+    );
+    assert_eq!(
+        views
+            .iter()
+            .map(|view| app.view_count(view))
+            .collect::<Vec<_>>(),
+        [3, 1, 1, 1, 0, 2]
+    );
+}
+
+#[test]
+fn switching_views_changes_the_list() {
+    let mut app = setup_app();
+    app.state.select(Some(2));
+    app.set_view(View::Project(String::from("work")));
+    assert_eq!(app.view(), [1, 2]);
+    // the selected task stays selected
+    assert_eq!(app.selected_id(), Some(2));
+
+    app.step_view(true);
+    assert_eq!(app.active_view, View::All);
+    app.step_view(false);
+    app.step_view(false);
+    assert_eq!(app.active_view, View::Project(String::from("home")));
+    assert!(app.view().is_empty());
+}
+
+#[test]
+fn saved_views_persist_in_the_config() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = temp_dir.path().join("config.json");
+    let mut app = setup_app();
+    app.config_path = Some(path.clone());
+
+    app.set_query("+work -bug").unwrap();
+    app.save_view().unwrap();
+    assert!(app.filter.is_empty());
+    assert_eq!(app.active_view.label(), "+work -bug");
+    assert_eq!(app.view(), [2]);
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(
+        config.views,
+        [SavedView {
+            name: String::from("+work -bug"),
+            query: String::from("+work -bug"),
+        }]
+    );
+
+    app.save_view().unwrap();
+    assert_eq!(app.active_view, View::All);
+    assert!(Config::load(&path).unwrap().views.is_empty());
+
+    std::fs::write(
+        &path,
+        r#"{ "views": [{ "name": "bad", "query": "due<" }] }"#,
+    )
+    .unwrap();
+    app.config = Config::load(&path).unwrap();
+    assert!(matches!(
+        app.config.view_errors()[..],
+        [TaskError::Validation { field: "views", .. }]
+    ));
+    assert_eq!(app.views().len(), 6);
+    // a broken view is kept when the config is written back
+    app.config.save(&path).unwrap();
+    assert_eq!(Config::load(&path).unwrap().views.len(), 1);
+}